@@ -0,0 +1,361 @@
+//! Derive macros for the `Encodable` and `Decodable` traits of the `trie-rs`
+//! rlp module.
+//!
+//! Structs encode as an RLP list of their fields, in declaration order. The
+//! following attributes are supported:
+//!
+//! - `#[rlp(skip)]` on a field leaves it out of the encoding. It decodes to
+//!   `Default::default()`.
+//! - `#[rlp(trailing)]` on a struct allows the trailing `Option` fields to be
+//!   omitted from the list. Fields are encoded up to the last one which is set,
+//!   and missing fields decode as `None`. Present fields decode as `Some`,
+//!   even if they hold zero. An unset field before a set one is encoded as
+//!   the zero value of its type, which must implement `Default`. This is how
+//!   Ethereum headers add fields in new forks.
+//! - `#[rlp(transparent)]` on a struct with a single field encodes the struct
+//!   as that field alone.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, parse_quote, spanned::Spanned, Attribute, Data, DeriveInput, Error, Fields,
+    GenericArgument, GenericParam, Generics, Index, Member, PathArguments, Type,
+};
+
+#[proc_macro_derive(RlpEncodable, attributes(rlp))]
+pub fn derive_encodable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_encodable(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(RlpDecodable, attributes(rlp))]
+pub fn derive_decodable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_decodable(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct StructAttrs {
+    trailing: bool,
+    transparent: bool,
+}
+
+struct Field {
+    member: Member,
+    ty: Type,
+    skip: bool,
+}
+
+fn parse_struct_attrs(attrs: &[Attribute]) -> syn::Result<StructAttrs> {
+    let mut parsed = StructAttrs::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("rlp")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("trailing") {
+                parsed.trailing = true;
+                Ok(())
+            } else if meta.path.is_ident("transparent") {
+                parsed.transparent = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown rlp struct attribute"))
+            }
+        })?;
+    }
+    if parsed.trailing && parsed.transparent {
+        return Err(Error::new(
+            attrs[0].span(),
+            "`trailing` and `transparent` can't be combined",
+        ));
+    }
+    Ok(parsed)
+}
+
+fn parse_fields(input: &DeriveInput) -> syn::Result<Vec<Field>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            input.ident.span(),
+            "RLP derives only support structs",
+        ));
+    };
+    let fields = match &data.fields {
+        Fields::Named(fields) => fields.named.iter().collect::<Vec<_>>(),
+        Fields::Unnamed(fields) => fields.unnamed.iter().collect(),
+        Fields::Unit => Vec::new(),
+    };
+
+    fields
+        .into_iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let mut skip = false;
+            for attr in field.attrs.iter().filter(|a| a.path().is_ident("rlp")) {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("skip") {
+                        skip = true;
+                        Ok(())
+                    } else {
+                        Err(meta.error("unknown rlp field attribute"))
+                    }
+                })?;
+            }
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(i)),
+            };
+            Ok(Field {
+                member,
+                ty: field.ty.clone(),
+                skip,
+            })
+        })
+        .collect()
+}
+
+/// Returns `T` if ty is an `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first()? {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_option(ty: &Type) -> bool {
+    option_inner(ty).is_some()
+}
+
+/// Returns the index of the first trailing optional field. All encoded
+/// fields from there on must be `Option`s.
+fn trailing_start(fields: &[&Field]) -> syn::Result<usize> {
+    let start = fields
+        .iter()
+        .position(|f| is_option(&f.ty))
+        .unwrap_or(fields.len());
+    if let Some(field) = fields[start..].iter().find(|f| !is_option(&f.ty)) {
+        return Err(Error::new(
+            field.ty.span(),
+            "fields after the first trailing Option must be Options",
+        ));
+    }
+    Ok(start)
+}
+
+fn transparent_field(input: &DeriveInput, fields: &[&Field]) -> syn::Result<Member> {
+    match fields {
+        [field] => Ok(field.member.clone()),
+        _ => Err(Error::new(
+            input.ident.span(),
+            "transparent structs must have exactly one encoded field",
+        )),
+    }
+}
+
+fn add_bound(generics: &Generics, bound: TokenStream2) -> Generics {
+    let mut generics = generics.clone();
+    for param in &mut generics.params {
+        if let GenericParam::Type(param) = param {
+            param.bounds.push(parse_quote!(#bound));
+        }
+    }
+    generics
+}
+
+fn expand_encodable(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let attrs = parse_struct_attrs(&input.attrs)?;
+    let fields = parse_fields(&input)?;
+    let encoded: Vec<&Field> = fields.iter().filter(|f| !f.skip).collect();
+
+    let (body, len) = if attrs.transparent {
+        let member = transparent_field(&input, &encoded)?;
+        (
+            quote! {
+                ::trie_rs::rlp::Encodable::encode(&self.#member, rlp_enc);
+            },
+            quote! {
+                ::trie_rs::rlp::Encodable::encoded_len(&self.#member)
+            },
+        )
+    } else if attrs.trailing {
+        let start = trailing_start(&encoded)?;
+        let required: Vec<&Member> = encoded[..start].iter().map(|f| &f.member).collect();
+        let optional: Vec<&Member> = encoded[start..].iter().map(|f| &f.member).collect();
+        let inner: Vec<&Type> = encoded[start..]
+            .iter()
+            .map(|f| option_inner(&f.ty).unwrap())
+            .collect();
+        let indices: Vec<usize> = (0..optional.len()).collect();
+        // Only the optional fields up to the last one which is set are
+        // encoded. Unset fields before it are encoded as the zero value of
+        // their type, like geth does for optional fields.
+        let last = quote! {
+            let mut last = 0;
+            #(if self.#optional.is_some() { last = #indices + 1; })*
+        };
+        (
+            quote! {
+                let offset = rlp_enc.list();
+                #(::trie_rs::rlp::Encodable::encode(&self.#required, rlp_enc);)*
+                #last
+                #(
+                    if #indices < last {
+                        match &self.#optional {
+                            Some(value) => ::trie_rs::rlp::Encodable::encode(value, rlp_enc),
+                            None => ::trie_rs::rlp::Encodable::encode(
+                                &<#inner as ::core::default::Default>::default(),
+                                rlp_enc,
+                            ),
+                        }
+                    }
+                )*
+                rlp_enc.list_end(offset);
+            },
+            quote! {
+                #last
+                let mut content = 0 #(+ ::trie_rs::rlp::Encodable::encoded_len(&self.#required))*;
+                #(
+                    if #indices < last {
+                        content += match &self.#optional {
+                            Some(value) => ::trie_rs::rlp::Encodable::encoded_len(value),
+                            None => ::trie_rs::rlp::Encodable::encoded_len(
+                                &<#inner as ::core::default::Default>::default(),
+                            ),
+                        };
+                    }
+                )*
+                ::trie_rs::rlp::list_len(content)
+            },
+        )
+    } else {
+        let members: Vec<&Member> = encoded.iter().map(|f| &f.member).collect();
+        (
+            quote! {
+                let offset = rlp_enc.list();
+                #(::trie_rs::rlp::Encodable::encode(&self.#members, rlp_enc);)*
+                rlp_enc.list_end(offset);
+            },
+            quote! {
+                ::trie_rs::rlp::list_len(0 #(+ ::trie_rs::rlp::Encodable::encoded_len(&self.#members))*)
+            },
+        )
+    };
+
+    let name = &input.ident;
+    let generics = add_bound(&input.generics, quote!(::trie_rs::rlp::Encodable));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::trie_rs::rlp::Encodable for #name #ty_generics #where_clause {
+            fn encode(&self, rlp_enc: &mut ::trie_rs::rlp::rlp_encoder::RlpEncoder) {
+                #body
+            }
+
+            fn encoded_len(&self) -> usize {
+                #len
+            }
+        }
+    })
+}
+
+fn expand_decodable(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let attrs = parse_struct_attrs(&input.attrs)?;
+    let fields = parse_fields(&input)?;
+    let encoded: Vec<&Field> = fields.iter().filter(|f| !f.skip).collect();
+    let trailing = if attrs.trailing {
+        trailing_start(&encoded)?
+    } else {
+        encoded.len()
+    };
+
+    let vars: Vec<_> = (0..fields.len())
+        .map(|i| format_ident!("field_{}", i))
+        .collect();
+    let mut position = 0;
+    let decode_fields = fields.iter().zip(&vars).map(|(field, var)| {
+        let ty = &field.ty;
+        if field.skip {
+            return quote! { let #var: #ty = ::core::default::Default::default(); };
+        }
+        position += 1;
+        if attrs.transparent {
+            quote! { let #var = <#ty as ::trie_rs::rlp::Decodable>::decode(buf)?; }
+        } else if position > trailing {
+            // A present field is always set, even if it holds a value whose
+            // encoding is the empty string, such as zero.
+            let inner = option_inner(ty).unwrap();
+            quote! {
+                let #var: #ty = if content.is_empty() {
+                    None
+                } else {
+                    Some(::trie_rs::rlp::decode_in::<#inner>(outer, &mut content)?)
+                };
+            }
+        } else {
+            quote! { let #var: #ty = ::trie_rs::rlp::decode_in(outer, &mut content)?; }
+        }
+    });
+    let decode_fields: Vec<_> = decode_fields.collect();
+
+    let construct = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(_) => {
+                let members = fields.iter().map(|f| &f.member);
+                quote! { Self { #(#members: #vars),* } }
+            }
+            Fields::Unnamed(_) => quote! { Self(#(#vars),*) },
+            Fields::Unit => quote! { Self },
+        },
+        _ => unreachable!(),
+    };
+
+    let body = if attrs.transparent {
+        transparent_field(&input, &encoded)?;
+        quote! {
+            #(#decode_fields)*
+            Ok(#construct)
+        }
+    } else {
+        quote! {
+            let outer = *buf;
+            let (mut content, rest) = ::trie_rs::rlp::decode::split_list(buf)?;
+            #(#decode_fields)*
+            if !content.is_empty() {
+                return Err(::trie_rs::rlp::DecodeError::TrailingBytes(
+                    outer.len() - rest.len() - content.len(),
+                ));
+            }
+            *buf = rest;
+            Ok(#construct)
+        }
+    };
+
+    // The input lifetime must outlive the lifetimes of the struct, so that
+    // borrowed fields can point into the input.
+    let name = &input.ident;
+    let mut generics = add_bound(&input.generics, quote!(::trie_rs::rlp::Decodable<'rlp>));
+    let lifetimes: Vec<_> = input.generics.lifetimes().map(|l| &l.lifetime).collect();
+    generics
+        .params
+        .insert(0, parse_quote!('rlp: #(#lifetimes)+*));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::trie_rs::rlp::Decodable<'rlp> for #name #ty_generics #where_clause {
+            fn decode(buf: &mut &'rlp [u8]) -> Result<Self, ::trie_rs::rlp::DecodeError> {
+                #body
+            }
+        }
+    })
+}
@@ -0,0 +1,60 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::trie::types::{Database, Hash, NodeSet, Reader, TrieNode};
+
+type NodeMap = HashMap<(Hash, Vec<u8>), TrieNode>;
+
+/// An in-memory, path-keyed node database. Nodes are stored by the owner of
+/// their trie and their path from the root, so only the latest version of
+/// every trie is available.
+#[derive(Default, Clone)]
+pub struct MemoryDatabase {
+    nodes: Rc<RefCell<NodeMap>>,
+}
+
+impl MemoryDatabase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes the nodes collected by a trie commit into the database.
+    pub fn update(&self, set: &NodeSet) {
+        let mut nodes = self.nodes.borrow_mut();
+        for (path, node) in &set.nodes {
+            nodes.insert((set.owner, path.clone()), node.clone());
+        }
+    }
+
+    /// Returns the number of stored nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.borrow().is_empty()
+    }
+}
+
+impl Database for MemoryDatabase {
+    fn reader(&self, _state_root: &Hash) -> Result<Box<dyn Reader>, std::io::Error> {
+        Ok(Box::new(MemoryReader {
+            nodes: self.nodes.clone(),
+        }))
+    }
+}
+
+struct MemoryReader {
+    nodes: Rc<RefCell<NodeMap>>,
+}
+
+impl Reader for MemoryReader {
+    /// Retrieves the node at path in the trie of owner. An error is returned
+    /// if the node is missing or was overwritten by a node with another hash.
+    fn node(&self, owner: Hash, path: Option<Vec<u8>>, hash: Hash) -> Result<Vec<u8>, ()> {
+        let nodes = self.nodes.borrow();
+        match nodes.get(&(owner, path.unwrap_or_default())) {
+            Some(node) if node.hash == hash => Ok(node.blob.clone()),
+            _ => Err(()),
+        }
+    }
+}
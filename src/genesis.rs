@@ -0,0 +1,309 @@
+use std::{collections::BTreeMap, fmt};
+
+use crate::{
+    database::MemoryDatabase,
+    trie::{
+        self,
+        trie_id::{storage_trie_id, trie_id},
+        types::{Address, Hash, EMPTY_ROOT_HASH},
+    },
+    types::{
+        account::{StateAccount, EMPTY_CODE_HASH},
+        u256::U256,
+    },
+    utils::{from_hex, hex_to_hash, json::Json, keccak256},
+};
+
+/// GenesisAccount is an account in the state of the genesis block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GenesisAccount {
+    pub code: Vec<u8>,
+    pub storage: BTreeMap<Hash, Hash>,
+    pub balance: U256,
+    pub nonce: u64,
+}
+
+/// GenesisAlloc specifies the initial state of a genesis block.
+pub type GenesisAlloc = BTreeMap<Address, GenesisAccount>;
+
+/// GenesisError is returned for a genesis file which can't be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenesisError {
+    /// The file is not valid JSON. Holds the byte offset of the error.
+    InvalidJson(usize),
+    /// The file has no `alloc` object.
+    MissingAlloc,
+    /// An address in alloc is not a 20 byte hex string.
+    InvalidAddress(String),
+    /// A field of the account at address is malformed.
+    InvalidField {
+        address: String,
+        field: &'static str,
+    },
+}
+
+impl fmt::Display for GenesisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenesisError::InvalidJson(offset) => {
+                write!(f, "genesis: invalid JSON at offset {}", offset)
+            }
+            GenesisError::MissingAlloc => f.write_str("genesis: missing alloc"),
+            GenesisError::InvalidAddress(address) => {
+                write!(f, "genesis: invalid address {:?}", address)
+            }
+            GenesisError::InvalidField { address, field } => {
+                write!(f, "genesis: invalid {} of account {}", field, address)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GenesisError {}
+
+/// Reads the `alloc` section of a geth genesis file. Addresses, storage keys
+/// and values, and code are hex strings, with or without a `0x` prefix.
+/// Balances and nonces are hex strings with a `0x` prefix, decimal strings or
+/// JSON numbers.
+pub fn parse_genesis_alloc(json: &str) -> Result<GenesisAlloc, GenesisError> {
+    let genesis = Json::parse(json).map_err(|e| GenesisError::InvalidJson(e.offset))?;
+    let alloc = genesis
+        .get("alloc")
+        .and_then(Json::as_object)
+        .ok_or(GenesisError::MissingAlloc)?;
+
+    let mut accounts = GenesisAlloc::new();
+    for (address, account) in alloc {
+        let parsed: Address = from_hex(address)
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| GenesisError::InvalidAddress(address.clone()))?;
+        let account = parse_account(account).map_err(|field| GenesisError::InvalidField {
+            address: address.clone(),
+            field,
+        })?;
+        accounts.insert(parsed, account);
+    }
+
+    Ok(accounts)
+}
+
+/// Parses an account, returning the name of the first malformed field on
+/// failure.
+fn parse_account(account: &Json) -> Result<GenesisAccount, &'static str> {
+    let mut parsed = GenesisAccount::default();
+    if let Some(balance) = account.get("balance") {
+        parsed.balance = parse_u256(balance).ok_or("balance")?;
+    }
+    if let Some(nonce) = account.get("nonce") {
+        parsed.nonce = parse_u256(nonce)
+            .and_then(|n| n.try_into().ok())
+            .ok_or("nonce")?;
+    }
+    if let Some(code) = account.get("code") {
+        parsed.code = code.as_str().and_then(from_hex).ok_or("code")?;
+    }
+    if let Some(storage) = account.get("storage") {
+        for (key, value) in storage.as_object().ok_or("storage")? {
+            let key = hex_to_hash(key).ok_or("storage")?;
+            let value = value.as_str().and_then(hex_to_hash).ok_or("storage")?;
+            parsed.storage.insert(key, value);
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn parse_u256(value: &Json) -> Option<U256> {
+    match value {
+        Json::String(s) => s.parse().ok(),
+        Json::Number(n) => U256::from_dec_str(n).ok(),
+        _ => None,
+    }
+}
+
+/// Builds the state trie of alloc and the storage tries of its accounts,
+/// and returns the state root. If db is given, the nodes of all tries are
+/// committed into it. The code of the accounts is not stored, only its hash.
+pub fn build_genesis_state(alloc: &GenesisAlloc, db: Option<&MemoryDatabase>) -> Hash {
+    // The tries start out empty and are only written to, so all their nodes
+    // are in memory and the trie operations can't fail.
+    const IN_MEMORY: &str = "genesis tries are held in memory";
+
+    let scratch = MemoryDatabase::new();
+    let target = db.unwrap_or(&scratch);
+
+    let mut state = trie::new(trie_id(EMPTY_ROOT_HASH), target).expect(IN_MEMORY);
+    for (address, account) in alloc {
+        let owner = keccak256(address);
+        let mut storage = trie::new(
+            storage_trie_id(EMPTY_ROOT_HASH, owner, EMPTY_ROOT_HASH),
+            target,
+        )
+        .expect(IN_MEMORY);
+        for (key, value) in &account.storage {
+            storage.update_storage(key, value).expect(IN_MEMORY);
+        }
+        let (storage_root, nodes) = storage.commit();
+        if let (Some(db), Some(nodes)) = (db, nodes) {
+            db.update(&nodes);
+        }
+
+        let code_hash = if account.code.is_empty() {
+            EMPTY_CODE_HASH
+        } else {
+            keccak256(&account.code)
+        };
+        let state_account = StateAccount {
+            nonce: account.nonce,
+            balance: account.balance,
+            storage_root,
+            code_hash,
+        };
+        state
+            .update_account(address, &state_account)
+            .expect(IN_MEMORY);
+    }
+
+    let (root, nodes) = state.commit();
+    if let (Some(db), Some(nodes)) = (db, nodes) {
+        db.update(&nodes);
+    }
+
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        trie::trie_id::storage_trie_id,
+        utils::{bytes_to_hash, to_hex, trim_left_zeroes},
+    };
+
+    fn state_root(json: &str) -> String {
+        let alloc = parse_genesis_alloc(json).unwrap();
+        to_hex(&build_genesis_state(&alloc, None))
+    }
+
+    #[test]
+    fn mainnet_state_root() {
+        assert_eq!(
+            state_root(include_str!("../testdata/genesis/mainnet.json")),
+            "d7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544"
+        );
+    }
+
+    #[test]
+    fn sepolia_state_root() {
+        assert_eq!(
+            state_root(include_str!("../testdata/genesis/sepolia.json")),
+            "5eb6e371a698b8d68f665192350ffcecbbbf322916f4b51bd79bb6887da3f494"
+        );
+    }
+
+    #[test]
+    fn holesky_state_root() {
+        assert_eq!(
+            state_root(include_str!("../testdata/genesis/holesky.json")),
+            "69d8c9d72f6fa4ad42d4702b433707212f90db395eb54dc20bc85de253788783"
+        );
+    }
+
+    #[test]
+    fn commit_to_database() {
+        let alloc = parse_genesis_alloc(include_str!("../testdata/genesis/holesky.json")).unwrap();
+        let db = MemoryDatabase::new();
+        let root = build_genesis_state(&alloc, Some(&db));
+        assert_eq!(root, build_genesis_state(&alloc, None));
+
+        // Read back an account with code and storage.
+        let (address, account) = alloc
+            .iter()
+            .find(|(_, account)| !account.storage.is_empty())
+            .unwrap();
+        let mut state = trie::new(trie_id(root), &db).unwrap();
+        let state_account = state.get_account(address).unwrap().unwrap();
+        assert_eq!(state_account.balance, account.balance);
+        assert_eq!(state_account.code_hash, keccak256(&account.code));
+
+        let owner = keccak256(address);
+        let id = storage_trie_id(root, owner, state_account.storage_root);
+        let mut storage = trie::new(id, &db).unwrap();
+        for (key, value) in &account.storage {
+            let stored = storage.get_storage(key).unwrap();
+            assert_eq!(stored.as_deref(), Some(trim_left_zeroes(value)));
+        }
+    }
+
+    #[test]
+    fn parse_accounts() {
+        let json = r#"{"alloc": {
+            "0x0000000000000000000000000000000000000001": {"balance": "0x10", "nonce": "2"},
+            "0000000000000000000000000000000000000002": {
+                "balance": 1000,
+                "code": "0x6000",
+                "storage": {"0x01": "0x02"}
+            }
+        }}"#;
+        let alloc = parse_genesis_alloc(json).unwrap();
+        let first = &alloc[&bytes_to_address(1)];
+        assert_eq!(first.balance, U256::from(16u64));
+        assert_eq!(first.nonce, 2);
+        let second = &alloc[&bytes_to_address(2)];
+        assert_eq!(second.balance, U256::from(1000u64));
+        assert_eq!(second.code, [0x60, 0x00]);
+        assert_eq!(second.storage[&bytes_to_hash(&[1])], bytes_to_hash(&[2]));
+
+        let account = "0x0000000000000000000000000000000000000001";
+        let invalid = |field| {
+            Err(GenesisError::InvalidField {
+                address: account.to_string(),
+                field,
+            })
+        };
+        for (json, err) in [
+            ("{\"alloc\": ", Err(GenesisError::InvalidJson(10))),
+            ("{}", Err(GenesisError::MissingAlloc)),
+            (
+                r#"{"alloc": {"0x01": {}}}"#,
+                Err(GenesisError::InvalidAddress("0x01".to_string())),
+            ),
+            (
+                r#"{"alloc": {"0x0000000000000000000000000000000000000001": {"balance": true}}}"#,
+                invalid("balance"),
+            ),
+            (
+                r#"{"alloc": {"0x0000000000000000000000000000000000000001": {"nonce": "0x10000000000000000"}}}"#,
+                invalid("nonce"),
+            ),
+            (
+                r#"{"alloc": {"0x0000000000000000000000000000000000000001": {"code": "0xzz"}}}"#,
+                invalid("code"),
+            ),
+            (
+                r#"{"alloc": {"0x0000000000000000000000000000000000000001": {"storage": {"0x01": "0x"}}}}"#,
+                Ok(()),
+            ),
+            (
+                r#"{"alloc": {"0x0000000000000000000000000000000000000001": {"storage": {"0x01": 2}}}}"#,
+                invalid("storage"),
+            ),
+            (
+                &format!(
+                    r#"{{"alloc": {{"{}": {{"storage": {{"0x{}": "0x"}}}}}}}}"#,
+                    account,
+                    "01".repeat(33)
+                ),
+                invalid("storage"),
+            ),
+        ] {
+            assert_eq!(parse_genesis_alloc(json).map(|_| ()), err, "{}", json);
+        }
+    }
+
+    fn bytes_to_address(b: u8) -> Address {
+        let mut address = [0; 20];
+        address[19] = b;
+        address
+    }
+}
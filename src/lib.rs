@@ -1,3 +1,10 @@
+// Lets the rlp derive macros refer to `::trie_rs` from inside this crate.
+extern crate self as trie_rs;
+
+pub mod database;
+pub mod genesis;
+pub mod light;
 pub mod trie;
 pub mod rlp;
-pub mod utils;
\ No newline at end of file
+pub mod types;
+pub mod utils;
@@ -0,0 +1,192 @@
+use crate::{
+    trie::{new_empty, proof::verify_proof, types::Hash, Trie},
+    types::{bloom::BLOOM_BIT_LENGTH, header::Header},
+    utils::bitutil::{compress_bytes, decompress_bytes},
+};
+
+// The number of blocks in a BloomTrie section.
+pub const BLOOM_TRIE_SECTION_SIZE: u64 = 32768;
+
+/// Returns the BloomTrie key of a bloom bit in a section, the big endian
+/// bit index followed by the big endian section index.
+pub fn bloom_trie_key(bit: u16, section: u64) -> [u8; 10] {
+    let mut key = [0; 10];
+    key[..2].copy_from_slice(&bit.to_be_bytes());
+    key[2..].copy_from_slice(&section.to_be_bytes());
+    key
+}
+
+/// Rotates the header blooms of a section into one bit vector per bloom
+/// bit. Bit i of vector b is set if the bloom of block i of the section has
+/// bit b set.
+fn bloom_bits(headers: &[Header]) -> Vec<Vec<u8>> {
+    let mut bits = vec![vec![0u8; headers.len().div_ceil(8)]; BLOOM_BIT_LENGTH];
+    for (n, header) in headers.iter().enumerate() {
+        let mask = 1 << (7 - n % 8);
+        for (b, vector) in bits.iter_mut().enumerate() {
+            // Bloom bit b is counted from the end of the filter.
+            let byte = header.bloom.0[header.bloom.0.len() - 1 - b / 8];
+            if byte & (1 << (b % 8)) != 0 {
+                vector[n / 8] |= mask;
+            }
+        }
+    }
+    bits
+}
+
+/// BloomTrieBuilder builds the BloomTrie from the canonical chain, one
+/// section at a time. For every section and bloom bit, the trie stores the
+/// compressed vector of that bit over the blocks of the section. Vectors
+/// without any set bit are not stored.
+pub struct BloomTrieBuilder {
+    trie: Trie,
+    section_size: u64,
+    sections: u64,
+}
+
+impl BloomTrieBuilder {
+    /// Creates a builder for sections of section_size blocks, which must not
+    /// be zero.
+    pub fn new(section_size: u64) -> Result<Self, ()> {
+        if section_size == 0 {
+            return Err(());
+        }
+        Ok(Self {
+            trie: new_empty(),
+            section_size,
+            sections: 0,
+        })
+    }
+
+    /// Returns the number of sections added so far.
+    pub fn sections(&self) -> u64 {
+        self.sections
+    }
+
+    /// Adds the next section of canonical headers, starting from the genesis
+    /// block, and returns the BloomTrie root for all sections added so far.
+    pub fn add_section(&mut self, headers: &[Header]) -> Result<Hash, ()> {
+        if headers.len() as u64 != self.section_size {
+            return Err(());
+        }
+        let first = self.sections * self.section_size;
+        if (0..).zip(headers).any(|(i, h)| h.number != first + i) {
+            return Err(());
+        }
+
+        for (bit, vector) in bloom_bits(headers).iter().enumerate() {
+            let comp = compress_bytes(vector);
+            if !comp.is_empty() {
+                let key = bloom_trie_key(bit as u16, self.sections);
+                self.trie.update(&key, &comp)?;
+            }
+        }
+        self.sections += 1;
+
+        Ok(self.trie.hash())
+    }
+
+    /// Returns the current BloomTrie root.
+    pub fn root(&mut self) -> Hash {
+        self.trie.hash()
+    }
+
+    /// Returns a proof of the bit vector which holds bloom bit of block
+    /// number.
+    pub fn prove(&self, bit: u16, number: u64) -> Result<Vec<Vec<u8>>, ()> {
+        self.trie
+            .prove(&bloom_trie_key(bit, number / self.section_size))
+    }
+}
+
+/// Verifies a BloomTrie proof against root, and returns whether bloom bit
+/// is set in the header bloom of block number. The section size must not be
+/// zero.
+pub fn verify_bloom_trie_proof(
+    root: Hash,
+    section_size: u64,
+    bit: u16,
+    number: u64,
+    proof: &[Vec<u8>],
+) -> Result<bool, ()> {
+    if section_size == 0 {
+        return Err(());
+    }
+    let key = bloom_trie_key(bit, number / section_size);
+    let Some(comp) = verify_proof(root, &key, proof)? else {
+        return Ok(false);
+    };
+    let vector = decompress_bytes(&comp, (section_size as usize).div_ceil(8))?;
+    let i = (number % section_size) as usize;
+    Ok(vector[i / 8] & (1 << (7 - i % 8)) != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns n headers. Block i sets bloom bit i, and every block sets the
+    /// last bloom bit.
+    fn headers(n: u64) -> Vec<Header> {
+        (0..n)
+            .map(|number| {
+                let mut header = Header {
+                    number,
+                    ..Default::default()
+                };
+                let bloom = &mut header.bloom.0;
+                bloom[bloom.len() - 1 - number as usize / 8] |= 1 << (number % 8);
+                bloom[0] |= 0x80;
+                header
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bit_vectors() {
+        let bits = bloom_bits(&headers(10));
+        assert_eq!(bits.len(), BLOOM_BIT_LENGTH);
+        assert_eq!(bits[0], [0x80, 0x00]);
+        assert_eq!(bits[9], [0x00, 0x40]);
+        assert_eq!(bits[10], [0x00, 0x00]);
+        assert_eq!(bits[BLOOM_BIT_LENGTH - 1], [0xFF, 0xC0]);
+    }
+
+    #[test]
+    fn build_and_prove() {
+        let section_size = 16;
+        let headers = headers(32);
+        let mut trie = BloomTrieBuilder::new(section_size).unwrap();
+        trie.add_section(&headers[..16]).unwrap();
+        let root = trie.add_section(&headers[16..]).unwrap();
+        assert_eq!(trie.sections(), 2);
+        assert_eq!(trie.root(), root);
+
+        let check = |bit: u16, number: u64| {
+            let proof = trie.prove(bit, number).unwrap();
+            verify_bloom_trie_proof(root, section_size, bit, number, &proof).unwrap()
+        };
+        assert!(check(3, 3));
+        assert!(!check(3, 4));
+        assert!(check(20, 20));
+        assert!(!check(20, 4));
+        assert!(check(BLOOM_BIT_LENGTH as u16 - 1, 17));
+        // The vector of bit 100 is never set, so it is not stored.
+        assert!(!check(100, 5));
+
+        let proof = trie.prove(3, 3).unwrap();
+        assert!(verify_bloom_trie_proof(root, section_size, 20, 20, &proof).is_err());
+    }
+
+    #[test]
+    fn invalid_sections() {
+        assert!(BloomTrieBuilder::new(0).is_err());
+        assert!(verify_bloom_trie_proof([0; 32], 0, 0, 0, &[]).is_err());
+
+        let headers = headers(8);
+        let mut trie = BloomTrieBuilder::new(4).unwrap();
+        assert!(trie.add_section(&headers[..3]).is_err());
+        assert!(trie.add_section(&headers[4..]).is_err());
+        assert_eq!(trie.sections(), 0);
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{
+    rlp::{self, RlpDecodable, RlpEncodable},
+    trie::{new_empty, proof::verify_proof, types::Hash, Trie},
+    types::{header::Header, u256::U256},
+};
+
+// The number of blocks in a CHT section.
+pub const CHT_SECTION_SIZE: u64 = 32768;
+
+/// ChtNode is the value stored in the CHT for every block.
+#[derive(Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct ChtNode {
+    pub hash: Hash,
+    pub td: U256, // total difficulty of the chain up to and including the block
+}
+
+/// Returns the CHT key of a block, its big endian number.
+pub fn cht_key(number: u64) -> [u8; 8] {
+    number.to_be_bytes()
+}
+
+/// ChtBuilder builds the CHT from the canonical chain, one section at a
+/// time. The trie is never reset, so the root after section n commits to all
+/// blocks of sections 0 to n.
+pub struct ChtBuilder {
+    trie: Trie,
+    section_size: u64,
+    sections: u64,
+    td: U256,
+    last_hash: Hash,
+}
+
+impl ChtBuilder {
+    /// Creates a builder for sections of section_size blocks, which must not
+    /// be zero.
+    pub fn new(section_size: u64) -> Result<Self, ()> {
+        if section_size == 0 {
+            return Err(());
+        }
+        Ok(Self {
+            trie: new_empty(),
+            section_size,
+            sections: 0,
+            td: U256::ZERO,
+            last_hash: [0; 32],
+        })
+    }
+
+    /// Returns the number of sections added so far.
+    pub fn sections(&self) -> u64 {
+        self.sections
+    }
+
+    /// Adds the next section of canonical headers, starting from the genesis
+    /// block, and returns the CHT root for all sections added so far. The
+    /// section must be complete and link to the previous section. An invalid
+    /// section is rejected as a whole, leaving the CHT unchanged.
+    pub fn add_section(&mut self, headers: &[Header]) -> Result<Hash, ()> {
+        if headers.len() as u64 != self.section_size {
+            return Err(());
+        }
+
+        // Validate the whole section before writing any of it.
+        let mut td = self.td;
+        let mut last_hash = self.last_hash;
+        let mut nodes = Vec::with_capacity(headers.len());
+        for (i, header) in headers.iter().enumerate() {
+            let number = self.sections * self.section_size + i as u64;
+            if header.number != number || (number > 0 && header.parent_hash != last_hash) {
+                return Err(());
+            }
+            td = td.checked_add(header.difficulty).ok_or(())?;
+            last_hash = header.hash();
+            nodes.push((
+                number,
+                ChtNode {
+                    hash: last_hash,
+                    td,
+                },
+            ));
+        }
+
+        for (number, node) in nodes {
+            self.trie
+                .update(&cht_key(number), &rlp::encode(&node))
+                .expect("updating a trie without a database cannot fail");
+        }
+        self.td = td;
+        self.last_hash = last_hash;
+        self.sections += 1;
+
+        Ok(self.trie.hash())
+    }
+
+    /// Returns the current CHT root.
+    pub fn root(&mut self) -> Hash {
+        self.trie.hash()
+    }
+
+    /// Returns a proof of the CHT entry of block number.
+    pub fn prove(&self, number: u64) -> Result<Vec<Vec<u8>>, ()> {
+        self.trie.prove(&cht_key(number))
+    }
+}
+
+/// Verifies a CHT proof for block number against root. Returns `Ok(None)` if
+/// the proof shows that the block is not in the CHT.
+pub fn verify_cht_proof(root: Hash, number: u64, proof: &[Vec<u8>]) -> Result<Option<ChtNode>, ()> {
+    match verify_proof(root, &cht_key(number), proof)? {
+        Some(value) => Ok(Some(rlp::decode(&value)?)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a chain of n headers, starting with the genesis block.
+    fn chain(n: u64) -> Vec<Header> {
+        let mut headers: Vec<Header> = Vec::new();
+        for number in 0..n {
+            headers.push(Header {
+                parent_hash: headers.last().map(Header::hash).unwrap_or_default(),
+                difficulty: U256::from(number + 1),
+                number,
+                ..Default::default()
+            });
+        }
+        headers
+    }
+
+    #[test]
+    fn build_and_prove() {
+        let headers = chain(8);
+        let mut cht = ChtBuilder::new(4).unwrap();
+        let first = cht.add_section(&headers[..4]).unwrap();
+        let root = cht.add_section(&headers[4..]).unwrap();
+        assert_ne!(first, root);
+        assert_eq!(cht.sections(), 2);
+        assert_eq!(cht.root(), root);
+
+        let mut td = U256::ZERO;
+        for header in &headers {
+            td += header.difficulty;
+            let proof = cht.prove(header.number).unwrap();
+            let node = verify_cht_proof(root, header.number, &proof).unwrap();
+            assert_eq!(
+                node,
+                Some(ChtNode {
+                    hash: header.hash(),
+                    td
+                })
+            );
+        }
+
+        // Blocks of later sections are not in the CHT.
+        let proof = cht.prove(8).unwrap();
+        assert_eq!(verify_cht_proof(root, 8, &proof), Ok(None));
+        let proof = cht.prove(3).unwrap();
+        assert!(verify_cht_proof(root, 2, &proof).is_err());
+    }
+
+    #[test]
+    fn invalid_sections() {
+        assert!(ChtBuilder::new(0).is_err());
+
+        let headers = chain(8);
+        let mut cht = ChtBuilder::new(4).unwrap();
+        assert!(cht.add_section(&headers[..3]).is_err());
+        assert!(cht.add_section(&headers[4..]).is_err());
+        let root = cht.add_section(&headers[..4]).unwrap();
+
+        // A broken link at the end of the section must not leave the blocks
+        // before it in the CHT.
+        let mut broken = headers[4..].to_vec();
+        broken[3].parent_hash = [0xFF; 32];
+        assert!(cht.add_section(&broken).is_err());
+        assert_eq!(cht.root(), root);
+        assert_eq!(cht.sections(), 1);
+        let proof = cht.prove(4).unwrap();
+        assert_eq!(verify_cht_proof(root, 4, &proof), Ok(None));
+
+        let mut overflow = headers[4..].to_vec();
+        overflow[0].difficulty = U256::MAX;
+        assert!(cht.add_section(&overflow).is_err());
+        assert_eq!(cht.root(), root);
+
+        let mut full = ChtBuilder::new(4).unwrap();
+        full.add_section(&headers[..4]).unwrap();
+        assert_eq!(
+            cht.add_section(&headers[4..]),
+            full.add_section(&headers[4..])
+        );
+    }
+}
@@ -0,0 +1,6 @@
+//! Tries which let light clients verify historical data: the Canonical Hash
+//! Trie (CHT) over block hashes and total difficulties, and the BloomTrie
+//! over the bloom bits of block headers.
+
+pub mod bloom_trie;
+pub mod cht;
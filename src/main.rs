@@ -1,3 +1,129 @@
+use std::{
+    env, fs,
+    io::{self, Read},
+    process,
+};
+
+use trie_rs::{
+    trie::{new_empty, proof::verify_proof, Trie},
+    utils::{bytes_to_hash, from_hex, to_hex},
+};
+
+const USAGE: &str = "Usage:
+  trie-rs root [file]                       print the root of the trie built from key/value pairs
+  trie-rs prove <key> [file]                print the proof nodes for key, one hex node per line
+  trie-rs verify <root> <key> <proof-file>  check a proof and print the value, or \"absent\"
+
+Key/value pairs are read from file, or stdin if omitted, as one hex encoded
+`key value` pair per line. Empty lines and lines starting with '#' are ignored.";
+
 fn main() {
-    println!("Hello, world!");
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let result = match args.as_slice() {
+        ["root"] => root(None),
+        ["root", file] => root(Some(file)),
+        ["prove", key] => prove(key, None),
+        ["prove", key, file] => prove(key, Some(file)),
+        ["verify", root, key, proof_file] => verify(root, key, proof_file),
+        ["help" | "-h" | "--help"] => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(USAGE.to_string()),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+fn root(file: Option<&str>) -> Result<(), String> {
+    let mut trie = build_trie(file)?;
+    println!("0x{}", to_hex(&trie.hash()));
+
+    Ok(())
+}
+
+fn prove(key: &str, file: Option<&str>) -> Result<(), String> {
+    let key = parse_hex(key)?;
+    let mut trie = build_trie(file)?;
+    trie.hash();
+
+    let proof = trie
+        .prove(&key)
+        .map_err(|_| "failed to construct proof".to_string())?;
+    for node in proof {
+        println!("0x{}", to_hex(&node));
+    }
+
+    Ok(())
+}
+
+fn verify(root: &str, key: &str, proof_file: &str) -> Result<(), String> {
+    let root = parse_hex(root)?;
+    if root.len() != 32 {
+        return Err(format!("invalid root length {}, want 32", root.len()));
+    }
+    let key = parse_hex(key)?;
+    let proof = read_input(Some(proof_file))?
+        .lines()
+        .filter(|line| !is_skipped(line))
+        .map(|line| parse_hex(line.trim()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match verify_proof(bytes_to_hash(&root), &key, &proof) {
+        Ok(Some(value)) => println!("0x{}", to_hex(&value)),
+        Ok(None) => println!("absent"),
+        Err(()) => return Err("invalid proof".to_string()),
+    }
+
+    Ok(())
+}
+
+/// Builds a trie from the key/value pairs in file, or stdin.
+fn build_trie(file: Option<&str>) -> Result<Trie, String> {
+    let input = read_input(file)?;
+    let mut trie = new_empty();
+
+    for (i, line) in input.lines().enumerate() {
+        if is_skipped(line) {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let (Some(key), Some(value), None) = (fields.next(), fields.next(), fields.next()) else {
+            return Err(format!("line {}: expected `key value`", i + 1));
+        };
+        let key = parse_hex(key).map_err(|err| format!("line {}: {}", i + 1, err))?;
+        let value = parse_hex(value).map_err(|err| format!("line {}: {}", i + 1, err))?;
+
+        trie.update(&key, &value)
+            .map_err(|_| format!("line {}: failed to insert key", i + 1))?;
+    }
+
+    Ok(trie)
+}
+
+fn read_input(file: Option<&str>) -> Result<String, String> {
+    match file {
+        Some(path) => fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err)),
+        None => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|err| format!("stdin: {}", err))?;
+            Ok(input)
+        }
+    }
+}
+
+fn is_skipped(line: &str) -> bool {
+    let line = line.trim();
+    line.is_empty() || line.starts_with('#')
+}
+
+fn parse_hex(s: &str) -> Result<Vec<u8>, String> {
+    from_hex(s).ok_or_else(|| format!("invalid hex string {:?}", s))
 }
@@ -1,12 +1,14 @@
+use super::DecodeError;
+
 /// Splits a Vector into the content of a list and any remaining bytes after the list.
 /// This function first attempts to split the input byte slice using the `split` function.
 /// If the resulting `Kind` is a `List`, it returns the content of the list and the
 /// remaining bytes. Otherwise, it returns an error.
-pub fn split_list(buff: &[u8]) -> Result<(&[u8], &[u8]), ()> {
-    let (kind, content, rest) = split(&buff[..])?;
+pub fn split_list(buff: &[u8]) -> Result<(&[u8], &[u8]), DecodeError> {
+    let (kind, content, rest) = split(buff)?;
 
     if kind != Kind::List {
-        return Err(());
+        return Err(DecodeError::UnexpectedString(0));
     }
 
     Ok((content, rest))
@@ -14,32 +16,35 @@ pub fn split_list(buff: &[u8]) -> Result<(&[u8], &[u8]), ()> {
 
 /// This function parses the input as RLP (Recursive Length Prefix) encoded data,
 /// extracts the first value, and returns it along with any remaining data.
-fn split(buff: &[u8]) -> Result<(Kind, &[u8], &[u8]), ()> {
+pub fn split(buff: &[u8]) -> Result<(Kind, &[u8], &[u8]), DecodeError> {
     let (k, ts, cs) = read_kind(buff)?;
 
-    let content_end = (ts + cs) as usize;
-    if content_end > buff.len() {
-        return Err(());
+    let content_end = ts.saturating_add(cs);
+    if content_end > buff.len() as u64 {
+        return Err(DecodeError::InputTooShort(0));
     }
+    let content_end = content_end as usize;
 
     Ok((
         k,
         &buff[ts as usize..content_end],
-        &buff[content_end as usize..],
+        &buff[content_end..],
     ))
 }
 
 /// CountValues counts the number of encoded values in b.
-pub fn count_values(mut buff: &[u8]) -> Result<u8, ()> {
+pub fn count_values(mut buff: &[u8]) -> Result<u8, DecodeError> {
     let mut i = 0;
+    let mut pos = 0;
     while !buff.is_empty() {
-        let (_, tagsize, size) = read_kind(buff)?;
+        let (_, tagsize, size) = read_kind(buff).map_err(|e| e.shift(pos))?;
 
-        let index = (tagsize + size) as usize;
-        if index > buff.len() {
-            return Err(());
+        let index = tagsize.saturating_add(size);
+        if index > buff.len() as u64 {
+            return Err(DecodeError::InputTooShort(pos));
         }
-        buff = &buff[index..];
+        buff = &buff[index as usize..];
+        pos += index as usize;
         i += 1;
     }
 
@@ -48,26 +53,26 @@ pub fn count_values(mut buff: &[u8]) -> Result<u8, ()> {
 
 /// This function assumes the input is RLP (Recursive Length Prefix) encoded data representing a string.
 /// It extracts the content of the string and separates it from any trailing data.
-pub fn split_string(buff: &[u8]) -> Result<(&[u8], &[u8]), ()> {
+pub fn split_string(buff: &[u8]) -> Result<(&[u8], &[u8]), DecodeError> {
     let (k, content, rest) = split(buff)?;
 
     if k == Kind::List {
-        return Err(());
+        return Err(DecodeError::UnexpectedList(0));
     }
 
     Ok((content, rest))
 }
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Kind {
     Byte,
     String,
     List,
 }
 
-fn read_kind(buff: &[u8]) -> Result<(Kind, u64, u64), ()> {
-    if buff.len() == 0 {
-        return Err(());
+pub(crate) fn read_kind(buff: &[u8]) -> Result<(Kind, u64, u64), DecodeError> {
+    if buff.is_empty() {
+        return Err(DecodeError::InputTooShort(0));
     }
 
     let b = buff[0];
@@ -78,7 +83,7 @@ fn read_kind(buff: &[u8]) -> Result<(Kind, u64, u64), ()> {
             let contentsize = (b - 0x80) as u64;
             // Reject strings that should've been single bytes.
             if contentsize == 1 && buff.len() > 1 && buff[1] < 128 {
-                return Err(());
+                return Err(DecodeError::NonCanonicalSize(0));
             }
             (Kind::String, 1, contentsize)
         }
@@ -98,9 +103,11 @@ fn read_kind(buff: &[u8]) -> Result<(Kind, u64, u64), ()> {
     Ok((k, tagsize, contentsize))
 }
 
-fn read_size(b: &[u8], slen: u8) -> Result<u64, ()> {
+/// Reads the size of a long string or list from the bytes after its tag.
+/// Errors are reported at offset 0, the start of the value.
+fn read_size(b: &[u8], slen: u8) -> Result<u64, DecodeError> {
     if b.len() < slen as usize {
-        return Err(());
+        return Err(DecodeError::InputTooShort(0));
     }
 
     let s = match slen {
@@ -142,21 +149,18 @@ fn read_size(b: &[u8], slen: u8) -> Result<u64, ()> {
                 | u64::from(b[6]) << 8
                 | u64::from(b[7])
         }
-        _ => return Err(()),
+        _ => unreachable!("size of size is always 1 to 8 bytes"),
     };
 
     // Reject sizes < 56 (shouldn't have separate size) and sizes with
     // leading zero bytes.
     if s < 56 || b[0] == 0 {
-        return Err(());
+        return Err(DecodeError::NonCanonicalSize(0));
     }
 
     Ok(s)
 }
 
-
-
-
 pub fn append_uint64(mut b: Vec<u8>, i: u64) -> Vec<u8> {
     if i == 0 {
         b.push(0x80);
@@ -179,7 +183,7 @@ pub fn append_uint64(mut b: Vec<u8>, i: u64) -> Vec<u8> {
                 b.push(0x84);
                 b.extend_from_slice(&i.to_be_bytes()[4..]);
             }
-            0x1_0000_0000..=0xFFFF_FFFF_FF => {
+            0x1_0000_0000..=0xFF_FFFF_FFFF => {
                 b.push(0x85);
                 b.extend_from_slice(&i.to_be_bytes()[3..]);
             }
@@ -187,7 +191,7 @@ pub fn append_uint64(mut b: Vec<u8>, i: u64) -> Vec<u8> {
                 b.push(0x86);
                 b.extend_from_slice(&i.to_be_bytes()[2..]);
             }
-            0x1_0000_0000_0000..=0xFFFF_FFFF_FFFF_FF => {
+            0x1_0000_0000_0000..=0xFF_FFFF_FFFF_FFFF => {
                 b.push(0x87);
                 b.extend_from_slice(&i.to_be_bytes()[1..]);
             }
@@ -5,12 +5,12 @@ pub struct Listhead {
 }
 
 impl Listhead {
-    pub fn encode(&self, buff: &[u8]) -> Vec<u8> {
-        let headput = self.put_head(&mut buff.to_vec(), 0xC0, 0xF7, self.size as u64);
-        return buff[..headput as usize].to_vec();
+    /// Writes the list header to the front of buff and returns its length.
+    pub fn encode(&self, buff: &mut [u8]) -> usize {
+        self.put_head(buff, 0xC0, 0xF7, self.size as u64) as usize
     }
 
-    pub fn put_head(&self, buff: &mut Vec<u8>, smalltag: u8, largetag: u8, size: u64) -> isize {
+    pub fn put_head(&self, buff: &mut [u8], smalltag: u8, largetag: u8, size: u64) -> isize {
         if size < 56 {
             buff[0] = smalltag + size as u8;
             return 1;
@@ -23,9 +23,31 @@ impl Listhead {
     }
 }
 
+/// header_len returns the length of the header of a string or list with
+/// content of the given size.
+pub fn header_len(size: usize) -> usize {
+    if size < 56 {
+        1
+    } else {
+        1 + intsize(size as u64)
+    }
+}
+
+/// intsize computes the minimum number of bytes required to store i.
+pub fn intsize(mut i: u64) -> usize {
+    let mut size = 1;
+    loop {
+        i >>= 8;
+        if i == 0 {
+            return size;
+        }
+        size += 1;
+    }
+}
+
 /// putint writes i to the beginning of b in big endian byte
 /// order, using the least number of bytes needed to represent i.
-fn putint(b: &mut [u8], i: u64) -> isize {
+pub fn putint(b: &mut [u8], i: u64) -> isize {
     match i {
         0..=0xFF => {
             b[0] = i as u8;
@@ -42,7 +64,7 @@ fn putint(b: &mut [u8], i: u64) -> isize {
             b[2] = i as u8;
             3
         }
-        0x1_0000_00..=0xFFFF_FFFF => {
+        0x100_0000..=0xFFFF_FFFF => {
             b[0] = (i >> 24) as u8;
             b[1] = (i >> 16) as u8;
             b[2] = (i >> 8) as u8;
@@ -57,7 +79,7 @@ fn putint(b: &mut [u8], i: u64) -> isize {
             b[4] = i as u8;
             5
         }
-        0x1_0000_0000_00..=0xFFFF_FFFF_FFFF => {
+        0x100_0000_0000..=0xFFFF_FFFF_FFFF => {
             b[0] = (i >> 40) as u8;
             b[1] = (i >> 32) as u8;
             b[2] = (i >> 24) as u8;
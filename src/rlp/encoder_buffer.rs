@@ -1,6 +1,6 @@
-use std::io::Read;
+use crate::utils::trim_left_zeroes;
 
-use super::encode::Listhead;
+use super::encode::{intsize, putint, Listhead};
 
 pub const EMPTY_STRING: [u8; 1] = [0x80];
 
@@ -26,61 +26,224 @@ impl RlpBuffer {
         let size = self.size();
         let lh = &mut self.lheads[index];
 
-        lh.size = size - lh.offset as usize - lh.size;
+        lh.size = size - lh.offset - lh.size;
 
         if lh.size < 56 {
             self.lhsize += 1; // length encoded into kind tag
         } else {
-            self.lhsize += 1 + lh.size;
+            self.lhsize += 1 + intsize(lh.size as u64);
         }
     }
 
     pub fn size(&self) -> usize {
-        return self.str.len() + self.lhsize;
+        self.str.len() + self.lhsize
     }
 
-    pub fn write_bytes(&mut self, bytes: Vec<u8>) {
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
         if bytes.len() == 1 && bytes[0] <= 0x7F {
             // fits single byte, no string header
             self.str.push(bytes[0]);
         } else {
             self.encode_string_header(bytes.len());
-            self.str = [self.str.clone(), bytes].concat();
+            self.str.extend_from_slice(bytes);
+        }
+    }
+
+    /// Writes i as an RLP string using the minimal big endian representation.
+    pub fn write_uint(&mut self, i: u64) {
+        if i == 0 {
+            self.str.push(0x80);
+        } else if i < 0x80 {
+            self.str.push(i as u8);
+        } else {
+            let s = putint(&mut self.size_buf[1..], i) as usize;
+            self.size_buf[0] = 0x80 + s as u8;
+            self.str.extend_from_slice(&self.size_buf[..s + 1]);
+        }
+    }
+
+    pub fn write_bool(&mut self, b: bool) {
+        if b {
+            self.str.push(0x01);
+        } else {
+            self.str.push(0x80);
+        }
+    }
+
+    /// Writes an unsigned integer of arbitrary size, given as big endian bytes.
+    /// Leading zero bytes are stripped so that the encoding is canonical.
+    pub fn write_big_int(&mut self, be_bytes: &[u8]) {
+        let bytes = trim_left_zeroes(be_bytes);
+        if bytes.len() <= 8 {
+            let i = bytes.iter().fold(0u64, |acc, &b| acc << 8 | u64::from(b));
+            self.write_uint(i);
+        } else {
+            self.encode_string_header(bytes.len());
+            self.str.extend_from_slice(bytes);
         }
     }
 
     pub fn write_string(&mut self, s: String) {
-        self.write_bytes(s.as_bytes().to_vec());
+        self.write_bytes(s.as_bytes());
     }
 
     pub fn write(&mut self, bytes: Vec<u8>) -> usize {
         let len = bytes.len();
-        self.str = [self.str.clone(), bytes].concat();
+        self.str.extend_from_slice(&bytes);
 
         len
     }
 
-    pub fn encode_string_header(&self, size: usize) {
-        unimplemented!()
+    pub fn encode_string_header(&mut self, size: usize) {
+        if size < 56 {
+            self.str.push(0x80 + size as u8);
+        } else {
+            let sizesize = putint(&mut self.size_buf[1..], size as u64) as usize;
+            self.size_buf[0] = 0xB7 + sizesize as u8;
+            self.str.extend_from_slice(&self.size_buf[..sizesize + 1]);
+        }
     }
 
-    pub fn copy_to(&self, dst: &mut Vec<u8>) {
+    /// Writes the complete encoding to dst, which must be exactly `size()` bytes long.
+    pub fn copy_to(&self, dst: &mut [u8]) {
         let mut strpos = 0;
         let mut pos = 0;
 
         for head in &self.lheads {
-            let src = &self.str[strpos..head.offset as usize];
-            dst[pos..].clone_from_slice(src);
-            let count = dst.len().min(src.len());
-            pos += count;
-            strpos += count;
-
-            //write header
-            let enc = head.encode(&dst[pos..]);
-            pos += enc.len();
+            // write string data before header
+            let src = &self.str[strpos..head.offset];
+            dst[pos..pos + src.len()].copy_from_slice(src);
+            pos += src.len();
+            strpos += src.len();
+
+            // write the header
+            pos += head.encode(&mut dst[pos..]);
         }
         // copy string data after the last list header
         let src = &self.str[strpos..];
         dst[pos..].copy_from_slice(src);
     }
+
+    /// Returns the complete encoding as a new byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![0; self.size()];
+        self.copy_to(&mut out);
+        out
+    }
+
+    pub fn reset(&mut self) {
+        self.str.clear();
+        self.lheads.clear();
+        self.lhsize = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::to_hex;
+
+    #[test]
+    fn uints() {
+        for (i, want) in [
+            (0u64, "80"),
+            (1, "01"),
+            (0x7F, "7f"),
+            (0x80, "8180"),
+            (0x0400, "820400"),
+            (u64::MAX, "88ffffffffffffffff"),
+        ] {
+            let mut buf = RlpBuffer::default();
+            buf.write_uint(i);
+            assert_eq!(to_hex(&buf.to_bytes()), want, "{}", i);
+        }
+    }
+
+    #[test]
+    fn bools_and_big_ints() {
+        let mut buf = RlpBuffer::default();
+        buf.write_bool(true);
+        buf.write_bool(false);
+        buf.write_big_int(&[0, 0, 0x04, 0x00]);
+        buf.write_big_int(&[0; 4]);
+        buf.write_big_int(&[0x01; 9]);
+        assert_eq!(to_hex(&buf.to_bytes()), "01808204008089010101010101010101");
+    }
+
+    #[test]
+    fn string_headers() {
+        let mut buf = RlpBuffer::default();
+        buf.write_bytes(&[0x7F]);
+        buf.write_bytes(&[0x80]);
+        buf.write_bytes(&[]);
+        buf.write_string("dog".to_string());
+        assert_eq!(to_hex(&buf.to_bytes()), "7f81808083646f67");
+
+        let mut buf = RlpBuffer::default();
+        buf.encode_string_header(55);
+        buf.encode_string_header(56);
+        buf.encode_string_header(1024);
+        assert_eq!(to_hex(&buf.to_bytes()), "b7b838b90400");
+    }
+
+    #[test]
+    fn lists() {
+        // [[], [[]], [[], [[]]]]
+        let mut buf = RlpBuffer::default();
+        let outer = buf.list();
+        let first = buf.list();
+        buf.list_end(first);
+        let second = buf.list();
+        let inner = buf.list();
+        buf.list_end(inner);
+        buf.list_end(second);
+        let third = buf.list();
+        let a = buf.list();
+        buf.list_end(a);
+        let b = buf.list();
+        let c = buf.list();
+        buf.list_end(c);
+        buf.list_end(b);
+        buf.list_end(third);
+        buf.list_end(outer);
+        assert_eq!(buf.size(), 8);
+        assert_eq!(to_hex(&buf.to_bytes()), "c7c0c1c0c3c0c1c0");
+    }
+
+    #[test]
+    fn long_lists() {
+        let mut buf = RlpBuffer::default();
+        let outer = buf.list();
+        for _ in 0..20 {
+            buf.write_string("dog".to_string());
+        }
+        buf.list_end(outer);
+        let enc = buf.to_bytes();
+        assert_eq!(buf.size(), 82);
+        assert_eq!(to_hex(&enc[..2]), "f850");
+        assert!(enc[2..].chunks(4).all(|c| c == b"\x83dog"));
+
+        // Header of a list with a long list inside.
+        let mut buf = RlpBuffer::default();
+        let outer = buf.list();
+        let inner = buf.list();
+        buf.write(vec![0x01; 1024]);
+        buf.list_end(inner);
+        buf.list_end(outer);
+        let enc = buf.to_bytes();
+        assert_eq!(enc.len(), buf.size());
+        assert_eq!(to_hex(&enc[..6]), "f90403f90400");
+    }
+
+    #[test]
+    fn reset() {
+        let mut buf = RlpBuffer::default();
+        let l = buf.list();
+        buf.write_uint(1024);
+        buf.list_end(l);
+        buf.reset();
+        assert_eq!(buf.size(), 0);
+        buf.write_uint(1);
+        assert_eq!(buf.to_bytes(), vec![0x01]);
+    }
 }
@@ -0,0 +1,145 @@
+use std::fmt;
+
+/// DecodeError describes why RLP input was rejected. Every variant carries
+/// the byte offset of the offending value, or of the unexpected data, in the
+/// input passed to the decoder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The size of a value is not encoded in its shortest form, e.g. a single
+    /// byte below 0x80 wrapped in a string header, or a long size below 56.
+    NonCanonicalSize(usize),
+    /// An integer has leading zero bytes.
+    NonCanonicalInteger(usize),
+    /// A value does not fit into the type it is decoded into.
+    ValueTooLarge(usize),
+    /// A value is not valid for the type it is decoded into, e.g. a bool
+    /// other than 0 or 1, or a string which is not UTF-8.
+    InvalidValue(usize),
+    /// A list was found where a string was expected.
+    UnexpectedList(usize),
+    /// A string was found where a list was expected.
+    UnexpectedString(usize),
+    /// The input ends before the value at the offset is complete.
+    InputTooShort(usize),
+    /// There is data left after the value, or inside a list after its last
+    /// expected element.
+    TrailingBytes(usize),
+    /// The end of a list was requested from a stream which is not in a list.
+    NotInList(usize),
+    /// Lists are nested deeper than the decoder allows. The offset is the one
+    /// of the first list past the limit.
+    TooDeep(usize),
+}
+
+impl DecodeError {
+    /// Returns the byte offset at which the error occurred.
+    pub fn offset(&self) -> usize {
+        match *self {
+            DecodeError::NonCanonicalSize(offset)
+            | DecodeError::NonCanonicalInteger(offset)
+            | DecodeError::ValueTooLarge(offset)
+            | DecodeError::InvalidValue(offset)
+            | DecodeError::UnexpectedList(offset)
+            | DecodeError::UnexpectedString(offset)
+            | DecodeError::InputTooShort(offset)
+            | DecodeError::TrailingBytes(offset)
+            | DecodeError::NotInList(offset)
+            | DecodeError::TooDeep(offset) => offset,
+        }
+    }
+
+    /// Moves the offset of the error by n bytes. Used when the error occurred
+    /// in a part of a larger input, such as the content of a list.
+    pub fn shift(self, n: usize) -> Self {
+        match self {
+            DecodeError::NonCanonicalSize(offset) => DecodeError::NonCanonicalSize(offset + n),
+            DecodeError::NonCanonicalInteger(offset) => {
+                DecodeError::NonCanonicalInteger(offset + n)
+            }
+            DecodeError::ValueTooLarge(offset) => DecodeError::ValueTooLarge(offset + n),
+            DecodeError::InvalidValue(offset) => DecodeError::InvalidValue(offset + n),
+            DecodeError::UnexpectedList(offset) => DecodeError::UnexpectedList(offset + n),
+            DecodeError::UnexpectedString(offset) => DecodeError::UnexpectedString(offset + n),
+            DecodeError::InputTooShort(offset) => DecodeError::InputTooShort(offset + n),
+            DecodeError::TrailingBytes(offset) => DecodeError::TrailingBytes(offset + n),
+            DecodeError::NotInList(offset) => DecodeError::NotInList(offset + n),
+            DecodeError::TooDeep(offset) => DecodeError::TooDeep(offset + n),
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            DecodeError::NonCanonicalSize(_) => "non-canonical size information",
+            DecodeError::NonCanonicalInteger(_) => "non-canonical integer (leading zero bytes)",
+            DecodeError::ValueTooLarge(_) => "value too large for target type",
+            DecodeError::InvalidValue(_) => "invalid value for target type",
+            DecodeError::UnexpectedList(_) => "expected string, found list",
+            DecodeError::UnexpectedString(_) => "expected list, found string",
+            DecodeError::InputTooShort(_) => "input too short",
+            DecodeError::TrailingBytes(_) => "unexpected trailing bytes",
+            DecodeError::NotInList(_) => "end of list outside of any list",
+            DecodeError::TooDeep(_) => "lists nested too deeply",
+        };
+        write!(f, "rlp: {} at offset {}", msg, self.offset())
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rlp::decode;
+
+    #[test]
+    fn offsets() {
+        assert_eq!(
+            decode::<u64>(&[0x81, 0x05]),
+            Err(DecodeError::NonCanonicalSize(0))
+        );
+        assert_eq!(
+            decode::<u64>(&[0x82, 0x00, 0x01]),
+            Err(DecodeError::NonCanonicalInteger(0))
+        );
+        assert_eq!(
+            decode::<u8>(&[0x82, 0x01, 0x00]),
+            Err(DecodeError::ValueTooLarge(0))
+        );
+        assert_eq!(decode::<bool>(&[0x02]), Err(DecodeError::InvalidValue(0)));
+        assert_eq!(decode::<u64>(&[0xC0]), Err(DecodeError::UnexpectedList(0)));
+        assert_eq!(
+            decode::<Vec<u64>>(&[0x80]),
+            Err(DecodeError::UnexpectedString(0))
+        );
+        assert_eq!(
+            decode::<u64>(&[0x83, 0x01]),
+            Err(DecodeError::InputTooShort(0))
+        );
+        assert_eq!(
+            decode::<u64>(&[0x01, 0x02]),
+            Err(DecodeError::TrailingBytes(1))
+        );
+        // Errors inside a list point at the element.
+        assert_eq!(
+            decode::<Vec<u64>>(&[0xC3, 0x01, 0x81, 0x02]),
+            Err(DecodeError::NonCanonicalSize(2))
+        );
+    }
+
+    #[test]
+    fn shift_and_display() {
+        let err = DecodeError::TrailingBytes(3).shift(4);
+        assert_eq!(err, DecodeError::TrailingBytes(7));
+        assert_eq!(err.offset(), 7);
+        assert_eq!(
+            err.to_string(),
+            "rlp: unexpected trailing bytes at offset 7"
+        );
+        assert_eq!(
+            DecodeError::NotInList(0).to_string(),
+            "rlp: end of list outside of any list at offset 0"
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use std::fmt::{self, Write};
+
+use crate::utils::{from_hex, json::Json, to_hex, trim_left_zeroes};
+
+use super::{
+    decode::{split, Kind},
+    rlp_encoder::RlpEncoder,
+    Decodable, DecodeError, Encodable,
+};
+
+/// The maximum nesting depth of lists in an RlpItem. Decoding deeper input
+/// fails with `DecodeError::TooDeep` instead of overflowing the stack.
+const MAX_DEPTH: usize = 128;
+
+/// RlpItem is a decoded RLP value of unknown structure. Any valid RLP input
+/// with lists nested at most MAX_DEPTH deep decodes into an RlpItem, which
+/// makes it useful for inspecting payloads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RlpItem {
+    Bytes(Vec<u8>),
+    List(Vec<RlpItem>),
+}
+
+impl Encodable for RlpItem {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        match self {
+            RlpItem::Bytes(bytes) => rlp_enc.write_bytes(bytes),
+            RlpItem::List(items) => {
+                let offset = rlp_enc.list();
+                for item in items {
+                    item.encode(rlp_enc);
+                }
+                rlp_enc.list_end(offset);
+            }
+        }
+    }
+}
+
+impl Decodable<'_> for RlpItem {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        Self::decode_nested(buf, 0)
+    }
+}
+
+/// Returns the UTF-8 interpretation of b if it is printable text.
+fn printable(b: &[u8]) -> Option<&str> {
+    let s = std::str::from_utf8(b).ok()?;
+    if s.is_empty() || s.chars().any(char::is_control) {
+        return None;
+    }
+    Some(s)
+}
+
+/// Returns the integer interpretation of b if it is a canonical integer of
+/// at most 128 bits.
+fn uint(b: &[u8]) -> Option<u128> {
+    if b.len() > 16 || b.first() == Some(&0) {
+        return None;
+    }
+    Some(b.iter().fold(0, |acc, &b| acc << 8 | u128::from(b)))
+}
+
+impl RlpItem {
+    /// Decodes an item which is nested in depth lists.
+    fn decode_nested(buf: &mut &[u8], depth: usize) -> Result<Self, DecodeError> {
+        let outer = *buf;
+        let (kind, mut content, rest) = split(buf)?;
+        let item = match kind {
+            Kind::Byte | Kind::String => RlpItem::Bytes(content.to_vec()),
+            Kind::List => {
+                if depth == MAX_DEPTH {
+                    return Err(DecodeError::TooDeep(0));
+                }
+                let mut items = Vec::new();
+                while !content.is_empty() {
+                    let offset = content.as_ptr() as usize - outer.as_ptr() as usize;
+                    let item = Self::decode_nested(&mut content, depth + 1)
+                        .map_err(|e| e.shift(offset))?;
+                    items.push(item);
+                }
+                RlpItem::List(items)
+            }
+        };
+        *buf = rest;
+
+        Ok(item)
+    }
+
+    /// Writes the item and its children, one item per line, with list
+    /// elements indented two spaces deeper than their list. Byte strings are
+    /// shown as hex, followed by their UTF-8 and integer interpretations
+    /// where those exist.
+    fn fstring(&self, f: &mut impl Write, ind: &str) -> fmt::Result {
+        match self {
+            RlpItem::Bytes(bytes) => {
+                write!(f, "bytes 0x{}", to_hex(bytes))?;
+                if let Some(s) = printable(bytes) {
+                    write!(f, " utf8={:?}", s)?;
+                }
+                if let Some(i) = uint(bytes) {
+                    write!(f, " uint={}", i)?;
+                }
+                writeln!(f)
+            }
+            RlpItem::List(items) => {
+                writeln!(f, "list [{}]", items.len())?;
+                for item in items {
+                    write!(f, "{}  ", ind)?;
+                    item.fstring(f, &format!("{}  ", ind))?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the item as JSON, with byte strings as 0x-prefixed hex strings
+    /// and lists as arrays.
+    pub fn to_json(&self) -> String {
+        match self {
+            RlpItem::Bytes(bytes) => format!("\"0x{}\"", to_hex(bytes)),
+            RlpItem::List(items) => {
+                let items: Vec<String> = items.iter().map(RlpItem::to_json).collect();
+                format!("[{}]", items.join(", "))
+            }
+        }
+    }
+
+    /// Parses an item from JSON made of nested arrays, strings and
+    /// non-negative integers. Strings starting with 0x are read as hex, other
+    /// strings as UTF-8 text. Integers are stored in their minimal big endian
+    /// form, so 0 becomes the empty string. Returns `None` for invalid JSON
+    /// and for JSON holding other values.
+    pub fn from_json(s: &str) -> Option<RlpItem> {
+        Self::from_json_value(&Json::parse(s).ok()?)
+    }
+
+    fn from_json_value(value: &Json) -> Option<RlpItem> {
+        match value {
+            Json::Array(items) => Some(RlpItem::List(
+                items
+                    .iter()
+                    .map(Self::from_json_value)
+                    .collect::<Option<_>>()?,
+            )),
+            Json::String(s) => match s.strip_prefix("0x") {
+                Some(hex) => Some(RlpItem::Bytes(from_hex(hex)?)),
+                None => Some(RlpItem::Bytes(s.clone().into_bytes())),
+            },
+            Json::Number(n) => {
+                let i: u128 = n.parse().ok()?;
+                Some(RlpItem::Bytes(trim_left_zeroes(&i.to_be_bytes()).to_vec()))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RlpItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fstring(f, "")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rlp::{decode, encode, encode::Listhead};
+
+    /// Returns the encoding of n empty lists nested in each other.
+    fn nested(n: usize) -> Vec<u8> {
+        let mut heads = Vec::new();
+        let mut size = 0;
+        for _ in 0..n {
+            let mut head = [0; 9];
+            let len = Listhead { offset: 0, size }.encode(&mut head);
+            heads.push(head[..len].to_vec());
+            size += len;
+        }
+        heads.into_iter().rev().flatten().collect()
+    }
+
+    #[test]
+    fn decode_items() {
+        let input = from_hex("c88363617483646f67c2c0c001").unwrap();
+        let want = RlpItem::List(vec![
+            RlpItem::Bytes(b"cat".to_vec()),
+            RlpItem::Bytes(b"dog".to_vec()),
+        ]);
+        assert_eq!(decode::<RlpItem>(&input[..9]).unwrap(), want);
+        assert_eq!(encode(&want), input[..9]);
+        assert_eq!(
+            decode::<RlpItem>(&input[9..]),
+            Err(DecodeError::TrailingBytes(3))
+        );
+        assert_eq!(
+            decode::<RlpItem>(&from_hex("c3c28101").unwrap()),
+            Err(DecodeError::NonCanonicalSize(2))
+        );
+    }
+
+    #[test]
+    fn depth_limit() {
+        let enc = nested(MAX_DEPTH);
+        let mut item = decode::<RlpItem>(&enc).unwrap();
+        for _ in 1..MAX_DEPTH {
+            let RlpItem::List(mut items) = item else {
+                panic!("expected a list");
+            };
+            item = items.pop().unwrap();
+        }
+        assert_eq!(item, RlpItem::List(Vec::new()));
+
+        // The innermost list is the last byte of the input.
+        let enc = nested(MAX_DEPTH + 1);
+        assert_eq!(
+            decode::<RlpItem>(&enc),
+            Err(DecodeError::TooDeep(enc.len() - 1))
+        );
+
+        // Deeply nested input must not overflow the stack.
+        let enc = nested(100_000);
+        assert!(matches!(
+            decode::<RlpItem>(&enc),
+            Err(DecodeError::TooDeep(_))
+        ));
+    }
+
+    #[test]
+    fn display_and_json() {
+        let item = RlpItem::from_json(r#"["cat", [1024, 0], "0x00ff"]"#).unwrap();
+        assert_eq!(
+            item.to_string(),
+            concat!(
+                "list [3]\n",
+                "  bytes 0x636174 utf8=\"cat\" uint=6513012\n",
+                "  list [2]\n",
+                "    bytes 0x0400 uint=1024\n",
+                "    bytes 0x uint=0\n",
+                "  bytes 0x00ff\n",
+            )
+        );
+        assert_eq!(
+            item.to_json(),
+            r#"["0x636174", ["0x0400", "0x"], "0x00ff"]"#
+        );
+        assert_eq!(RlpItem::from_json(&item.to_json()).unwrap(), item);
+        assert!(RlpItem::from_json("[true]").is_none());
+        assert!(RlpItem::from_json("-1").is_none());
+    }
+}
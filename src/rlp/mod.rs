@@ -1,4 +1,25 @@
 pub mod encoder_buffer;
 pub mod rlp_encoder;
 mod encode;
-pub mod decode;
\ No newline at end of file
+pub mod decode;
+mod error;
+mod item;
+mod raw;
+mod reader;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod stream;
+mod traits;
+
+pub use error::DecodeError;
+pub use item::RlpItem;
+pub use raw::{RawRlp, RlpList, RlpListIter};
+pub use reader::{ReadError, RlpReader};
+#[cfg(feature = "serde")]
+pub use self::serde::{from_slice, to_vec};
+pub use stream::RlpStream;
+pub use rlp_encoder::encode_to;
+pub use traits::{
+    bytes_len, decode, decode_in, encode, list_len, string_len, Decodable, Encodable,
+};
+pub use trie_rs_derive::{RlpDecodable, RlpEncodable};
@@ -0,0 +1,274 @@
+use super::{
+    decode::{split, Kind},
+    rlp_encoder::RlpEncoder,
+    traits::decode_in,
+    Decodable, DecodeError, Encodable,
+};
+
+/// RawRlp is the complete encoding of a single RLP value, borrowed from the
+/// input. Decoding a RawRlp only checks the header of the value, so it can be
+/// used to skip over values or to decode them later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawRlp<'a> {
+    raw: &'a [u8],
+    kind: Kind,
+    content: &'a [u8],
+}
+
+impl<'a> RawRlp<'a> {
+    /// Checks that raw contains exactly one RLP value.
+    pub fn new(raw: &'a [u8]) -> Result<Self, DecodeError> {
+        super::decode(raw)
+    }
+
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    /// Returns the content of the value, without its header.
+    pub fn content(&self) -> &'a [u8] {
+        self.content
+    }
+
+    /// Returns the complete encoding of the value.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Returns a lazy view of the value if it is a list.
+    pub fn as_list(&self) -> Result<RlpList<'a>, DecodeError> {
+        if self.kind != Kind::List {
+            return Err(DecodeError::UnexpectedString(0));
+        }
+        Ok(RlpList {
+            raw: self.raw,
+            content: self.content,
+        })
+    }
+
+    /// Decodes the value. Error offsets are relative to the start of the
+    /// value.
+    pub fn decode<T: Decodable<'a>>(&self) -> Result<T, DecodeError> {
+        super::decode(self.raw)
+    }
+}
+
+impl<'de: 'a, 'a> Decodable<'de> for RawRlp<'a> {
+    fn decode(buf: &mut &'de [u8]) -> Result<Self, DecodeError> {
+        let input = *buf;
+        let (kind, content, rest) = split(input)?;
+        *buf = rest;
+
+        Ok(RawRlp {
+            raw: &input[..input.len() - rest.len()],
+            kind,
+            content,
+        })
+    }
+}
+
+impl Encodable for RawRlp<'_> {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        rlp_enc.write(self.raw.to_vec());
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.raw.len()
+    }
+}
+
+/// RlpList is a lazy view of an RLP list. The elements are only split off
+/// the content when they are accessed, and are returned as RawRlp values
+/// which can be decoded individually.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RlpList<'a> {
+    raw: &'a [u8],
+    content: &'a [u8],
+}
+
+impl<'a> RlpList<'a> {
+    /// Checks that raw contains exactly one RLP list.
+    pub fn new(raw: &'a [u8]) -> Result<Self, DecodeError> {
+        super::decode(raw)
+    }
+
+    /// Returns the content of the list, without its header.
+    pub fn content(&self) -> &'a [u8] {
+        self.content
+    }
+
+    /// Returns the complete encoding of the list.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.content.is_empty()
+    }
+
+    /// Returns an iterator over the elements. Error offsets are relative to
+    /// the start of the list. The iterator ends after the first error.
+    pub fn iter(&self) -> RlpListIter<'a> {
+        RlpListIter {
+            outer: self.raw,
+            rest: self.content,
+        }
+    }
+
+    /// Returns the number of elements. The headers of all elements are read.
+    pub fn len(&self) -> Result<usize, DecodeError> {
+        self.iter().try_fold(0, |n, item| item.map(|_| n + 1))
+    }
+
+    /// Returns element i, or `None` if the list has fewer elements. Only the
+    /// headers of the elements before i are read.
+    pub fn get(&self, i: usize) -> Result<Option<RawRlp<'a>>, DecodeError> {
+        self.iter().nth(i).transpose()
+    }
+
+    /// Decodes element i, see `get`.
+    pub fn decode_at<T: Decodable<'a>>(&self, i: usize) -> Result<Option<T>, DecodeError> {
+        match self.get(i)? {
+            Some(item) => {
+                let offset = item.raw.as_ptr() as usize - self.raw.as_ptr() as usize;
+                item.decode().map(Some).map_err(|e| e.shift(offset))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'de: 'a, 'a> Decodable<'de> for RlpList<'a> {
+    fn decode(buf: &mut &'de [u8]) -> Result<Self, DecodeError> {
+        let raw = <RawRlp as Decodable>::decode(buf)?;
+        raw.as_list()
+    }
+}
+
+impl Encodable for RlpList<'_> {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        rlp_enc.write(self.raw.to_vec());
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.raw.len()
+    }
+}
+
+impl<'a> IntoIterator for &RlpList<'a> {
+    type Item = Result<RawRlp<'a>, DecodeError>;
+    type IntoIter = RlpListIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the elements of an RlpList.
+pub struct RlpListIter<'a> {
+    outer: &'a [u8],
+    rest: &'a [u8],
+}
+
+impl<'a> Iterator for RlpListIter<'a> {
+    type Item = Result<RawRlp<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let item = decode_in(self.outer, &mut self.rest);
+        if item.is_err() {
+            self.rest = &[];
+        }
+        Some(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        rlp::{decode, encode},
+        utils::from_hex,
+    };
+
+    #[test]
+    fn raw_values() {
+        let input = from_hex("c883636174820400c0").unwrap();
+        let pair = from_hex("c482040080").unwrap();
+        let (raw, rest): (RawRlp, u64) = decode(&pair).unwrap();
+        assert_eq!(raw.kind(), Kind::String);
+        assert_eq!(raw.as_bytes(), [0x82, 0x04, 0x00]);
+        assert_eq!(raw.content(), [0x04, 0x00]);
+        assert_eq!(raw.decode::<u64>().unwrap(), 1024);
+        assert_eq!(rest, 0);
+
+        let raw = RawRlp::new(&input).unwrap();
+        assert_eq!(raw.kind(), Kind::List);
+        assert_eq!(encode(&raw), input);
+        assert_eq!(raw.encoded_len(), input.len());
+        assert_eq!(
+            raw.decode::<(String, u64, Vec<u64>)>().unwrap(),
+            ("cat".to_string(), 1024, Vec::new())
+        );
+        assert_eq!(
+            RawRlp::new(&[0x01, 0x02]),
+            Err(DecodeError::TrailingBytes(1))
+        );
+        assert_eq!(
+            RawRlp::new(&[0x05]).unwrap().as_list(),
+            Err(DecodeError::UnexpectedString(0))
+        );
+    }
+
+    #[test]
+    fn lists() {
+        let input = from_hex("c883636174820400c0").unwrap();
+        let list = RlpList::new(&input).unwrap();
+        assert!(!list.is_empty());
+        assert_eq!(list.len().unwrap(), 3);
+        assert_eq!(list.content(), &input[1..]);
+        assert_eq!(list.as_bytes(), input);
+        assert_eq!(list.get(1).unwrap().unwrap().as_bytes(), [0x82, 0x04, 0x00]);
+        assert_eq!(list.get(3).unwrap(), None);
+        assert_eq!(list.decode_at::<&str>(0).unwrap(), Some("cat"));
+        assert_eq!(list.decode_at::<u64>(1).unwrap(), Some(1024));
+        assert_eq!(
+            list.decode_at::<u64>(2),
+            Err(DecodeError::UnexpectedList(8))
+        );
+        let kinds: Vec<Kind> = list.iter().map(|item| item.unwrap().kind()).collect();
+        assert_eq!(kinds, [Kind::String, Kind::String, Kind::List]);
+        assert_eq!(encode(&list), input);
+
+        assert!(RlpList::new(&[0xC0]).unwrap().is_empty());
+        assert_eq!(RlpList::new(&[0x80]), Err(DecodeError::UnexpectedString(0)));
+    }
+
+    #[test]
+    fn iter_stops_after_error() {
+        // The second element claims more bytes than the list holds.
+        let input = from_hex("c401830102").unwrap();
+        let list = RlpList::new(&input).unwrap();
+        let items: Vec<_> = list.iter().collect();
+        assert_eq!(items[1], Err(DecodeError::InputTooShort(2)));
+        assert_eq!(items.len(), 2);
+
+        let input = from_hex("c401820001").unwrap();
+        let list = RlpList::new(&input).unwrap();
+        let items: Vec<_> = list.iter().collect();
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            list.decode_at::<u64>(1),
+            Err(DecodeError::NonCanonicalInteger(2))
+        );
+
+        let input = from_hex("c3018105").unwrap();
+        let list = RlpList::new(&input).unwrap();
+        let items: Vec<_> = list.iter().collect();
+        assert_eq!(items[1], Err(DecodeError::NonCanonicalSize(2)));
+        assert_eq!(items.len(), 2);
+        assert_eq!(list.len(), Err(DecodeError::NonCanonicalSize(2)));
+    }
+}
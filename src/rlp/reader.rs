@@ -0,0 +1,213 @@
+use std::{
+    fmt,
+    io::{self, Read},
+};
+
+use super::{
+    decode::{read_kind, split},
+    Decodable, DecodeError,
+};
+
+/// ReadError is returned when reading RLP values from an `io::Read`.
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    /// The input is not valid RLP. Offsets are relative to the start of the
+    /// stream.
+    Decode(DecodeError),
+    /// The value at offset declares a size above the limit of the reader.
+    TooLarge {
+        offset: u64,
+        size: u64,
+        limit: u64,
+    },
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(err) => write!(f, "rlp: {}", err),
+            ReadError::Decode(err) => err.fmt(f),
+            ReadError::TooLarge {
+                offset,
+                size,
+                limit,
+            } => write!(
+                f,
+                "rlp: value size {} exceeds limit {} at offset {}",
+                size, limit, offset
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadError::Io(err) => Some(err),
+            ReadError::Decode(err) => Some(err),
+            ReadError::TooLarge { .. } => None,
+        }
+    }
+}
+
+/// RlpReader reads consecutive RLP values from an `io::Read`, such as a file
+/// of exported blocks. The header of each value is read first, and values
+/// with an encoded size above the limit are rejected before their content is
+/// read or any memory is allocated for it.
+///
+/// The reader issues small reads for the headers, so r should be buffered.
+pub struct RlpReader<R> {
+    r: R,
+    limit: u64,
+    pos: u64, // offset of the next value in the stream
+}
+
+impl<R: Read> RlpReader<R> {
+    /// Creates a reader which accepts values of at most limit bytes,
+    /// including their header.
+    pub fn new(r: R, limit: u64) -> Self {
+        Self { r, limit, pos: 0 }
+    }
+
+    /// Returns the offset of the next value in the stream.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    pub fn into_inner(self) -> R {
+        self.r
+    }
+
+    /// Reads the complete encoding of the next value. Returns `None` if the
+    /// stream ends before the next value.
+    pub fn read_raw(&mut self) -> Result<Option<Vec<u8>>, ReadError> {
+        let start = self.pos;
+        let mut head = [0; 9];
+        match self.r.read_exact(&mut head[..1]) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(ReadError::Io(err)),
+        }
+
+        // Read the size bytes of long strings and lists.
+        let sizesize = match head[0] {
+            0xB8..=0xBF => (head[0] - 0xB7) as usize,
+            0xF8..=0xFF => (head[0] - 0xF7) as usize,
+            _ => 0,
+        };
+        self.read_exact(&mut head[1..1 + sizesize], start)?;
+
+        let (_, tagsize, size) =
+            read_kind(&head[..1 + sizesize]).map_err(|e| self.decode_error(e, start))?;
+        let total = tagsize.saturating_add(size);
+        if total > self.limit {
+            return Err(ReadError::TooLarge {
+                offset: start,
+                size: total,
+                limit: self.limit,
+            });
+        }
+
+        let mut raw = vec![0; total as usize];
+        raw[..1 + sizesize].copy_from_slice(&head[..1 + sizesize]);
+        self.read_exact(&mut raw[1 + sizesize..], start)?;
+        self.pos += total;
+
+        // Run the canonical encoding checks which need the content.
+        split(&raw).map_err(|e| self.decode_error(e, start))?;
+
+        Ok(Some(raw))
+    }
+
+    /// Reads and decodes the next value. Returns `None` if the stream ends
+    /// before the next value.
+    pub fn read<T: for<'a> Decodable<'a>>(&mut self) -> Result<Option<T>, ReadError> {
+        let start = self.pos;
+        match self.read_raw()? {
+            Some(raw) => super::decode(&raw)
+                .map(Some)
+                .map_err(|e| self.decode_error(e, start)),
+            None => Ok(None),
+        }
+    }
+
+    /// Fills buf from the stream, treating the end of the stream as a
+    /// truncated value starting at offset start.
+    fn read_exact(&mut self, buf: &mut [u8], start: u64) -> Result<(), ReadError> {
+        self.r.read_exact(buf).map_err(|err| {
+            if err.kind() == io::ErrorKind::UnexpectedEof {
+                self.decode_error(DecodeError::InputTooShort(0), start)
+            } else {
+                ReadError::Io(err)
+            }
+        })
+    }
+
+    fn decode_error(&self, err: DecodeError, start: u64) -> ReadError {
+        ReadError::Decode(err.shift(start as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::from_hex;
+
+    #[test]
+    fn read_values() {
+        // 1024, "dog", [1, 2], then a value of 60 bytes.
+        let mut input = from_hex("82040083646f67c20102b83a").unwrap();
+        input.extend_from_slice(&[0xAA; 58]);
+        let mut r = RlpReader::new(input.as_slice(), 1024);
+        assert_eq!(r.read::<u64>().unwrap(), Some(1024));
+        assert_eq!(r.position(), 3);
+        assert_eq!(r.read::<String>().unwrap(), Some("dog".to_string()));
+        assert_eq!(r.read_raw().unwrap(), Some(vec![0xC2, 0x01, 0x02]));
+        assert_eq!(r.read::<Vec<u8>>().unwrap(), Some(vec![0xAA; 58]));
+        assert_eq!(r.position(), input.len() as u64);
+        assert_eq!(r.read::<u64>().unwrap(), None);
+    }
+
+    #[test]
+    fn limit() {
+        let input = from_hex("0183646f67").unwrap();
+        let mut r = RlpReader::new(input.as_slice(), 3);
+        assert_eq!(r.read::<u8>().unwrap(), Some(1));
+        assert!(matches!(
+            r.read_raw(),
+            Err(ReadError::TooLarge {
+                offset: 1,
+                size: 4,
+                limit: 3
+            })
+        ));
+
+        // The size is rejected before the content is read.
+        let input = from_hex("bf7fffffffffffffff").unwrap();
+        let mut r = RlpReader::new(input.as_slice(), 1 << 20);
+        assert!(matches!(
+            r.read_raw(),
+            Err(ReadError::TooLarge { offset: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn decode_errors() {
+        let read = |hex: &str| {
+            let input = from_hex(hex).unwrap();
+            let mut r = RlpReader::new(input.as_slice(), 1024);
+            r.read::<u64>().unwrap();
+            match r.read::<u64>() {
+                Err(ReadError::Decode(err)) => err,
+                other => panic!("unexpected result {:?}", other),
+            }
+        };
+        assert_eq!(read("018205"), DecodeError::InputTooShort(1));
+        assert_eq!(read("01b8"), DecodeError::InputTooShort(1));
+        assert_eq!(read("018105"), DecodeError::NonCanonicalSize(1));
+        assert_eq!(read("01b80105"), DecodeError::NonCanonicalSize(1));
+        assert_eq!(read("01820001"), DecodeError::NonCanonicalInteger(1));
+        assert_eq!(read("01c0"), DecodeError::UnexpectedList(1));
+    }
+}
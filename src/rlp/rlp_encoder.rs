@@ -1,36 +1,332 @@
-use super::encoder_buffer::RlpBuffer;
+use std::io;
 
-#[derive(Default,Clone)]
-pub struct RlpEncoder {
+use super::{
+    encode::{header_len, Listhead},
+    encoder_buffer::RlpBuffer,
+};
+use crate::utils::trim_left_zeroes;
+
+/// RlpEncoder is the target of `Encodable::encode`. By default it collects
+/// the encoding in an RlpBuffer. It can also only count the encoded bytes, see
+/// `count_encoded_len`, or write the encoding straight into an `io::Write`,
+/// see `encode_to`.
+#[derive(Default)]
+pub struct RlpEncoder<'w> {
     pub buffer: RlpBuffer,
+    sink: Sink<'w>,
+}
+
+#[derive(Default)]
+enum Sink<'w> {
+    #[default]
+    Buffer,
+    // The size of the encoding so far. The index of an open list is the size
+    // at which it was started, so counting needs no allocation.
+    Length(usize),
+    Counter(Counter),
+    Writer(Writer<'w>),
+}
+
+/// Counter tracks the size of the encoding and the content size of every
+/// list, in the order the lists are started.
+#[derive(Default)]
+struct Counter {
+    size: usize,
+    lists: Vec<usize>, // start offset while the list is open, content size after
+}
+
+/// Writer writes the encoding to w. The list headers are written from the
+/// sizes recorded by a Counter over the same value.
+struct Writer<'w> {
+    w: &'w mut dyn io::Write,
+    lists: Vec<usize>,
+    next_list: usize,
+    err: Option<io::Error>,
 }
 
-impl RlpEncoder {
+impl<'w> RlpEncoder<'w> {
+    /// Returns an encoder which only counts the size of the encoding.
+    fn length() -> Self {
+        Self {
+            buffer: RlpBuffer::default(),
+            sink: Sink::Length(0),
+        }
+    }
+
+    /// Returns an encoder which counts the size of the encoding and records
+    /// the content size of every list.
+    fn counter() -> Self {
+        Self {
+            buffer: RlpBuffer::default(),
+            sink: Sink::Counter(Counter::default()),
+        }
+    }
+
+    /// Returns an encoder which writes into w, using the list sizes measured
+    /// by a counter.
+    fn writer(w: &'w mut dyn io::Write, lists: Vec<usize>) -> Self {
+        Self {
+            buffer: RlpBuffer::default(),
+            sink: Sink::Writer(Writer {
+                w,
+                lists,
+                next_list: 0,
+                err: None,
+            }),
+        }
+    }
+
+    /// Appends raw bytes to a counter or writer. Writes after an error are
+    /// dropped, the error is reported by `encode_to`.
+    fn put(&mut self, b: &[u8]) {
+        match &mut self.sink {
+            Sink::Buffer => unreachable!("buffered encoders write into the buffer"),
+            Sink::Length(size) => *size += b.len(),
+            Sink::Counter(c) => c.size += b.len(),
+            Sink::Writer(wr) => {
+                if wr.err.is_none() {
+                    if let Err(err) = wr.w.write_all(b) {
+                        wr.err = Some(err);
+                    }
+                }
+            }
+        }
+    }
+
+    fn put_string_header(&mut self, size: usize) {
+        let mut head = [0; 9];
+        let n = Listhead::default().put_head(&mut head, 0x80, 0xB7, size as u64) as usize;
+        self.put(&head[..n]);
+    }
+
     pub fn write(&mut self, bytes: Vec<u8>) -> usize {
-        self.buffer.write(bytes)
+        match self.sink {
+            Sink::Buffer => self.buffer.write(bytes),
+            _ => {
+                self.put(&bytes);
+                bytes.len()
+            }
+        }
+    }
+
+    /// Appends bytes which are already RLP encoded, like `write`, without
+    /// taking ownership of them.
+    pub fn write_raw(&mut self, bytes: &[u8]) {
+        match self.sink {
+            Sink::Buffer => self.buffer.str.extend_from_slice(bytes),
+            _ => self.put(bytes),
+        }
+    }
+
+    /// Writes the header of a byte string of the given size. The content must
+    /// follow with `write_raw` or as the encoding of other values, as in
+    /// typed transaction envelopes.
+    pub fn write_string_header(&mut self, size: usize) {
+        match self.sink {
+            Sink::Buffer => self.buffer.encode_string_header(size),
+            _ => self.put_string_header(size),
+        }
     }
 
     pub fn list(&mut self) -> usize {
-        self.buffer.list()
+        match &mut self.sink {
+            Sink::Buffer => self.buffer.list(),
+            Sink::Length(size) => *size,
+            Sink::Counter(c) => {
+                c.lists.push(c.size);
+                c.lists.len() - 1
+            }
+            Sink::Writer(wr) => {
+                let size = wr.lists[wr.next_list];
+                wr.next_list += 1;
+                let mut head = [0; 9];
+                let n = Listhead { offset: 0, size }.encode(&mut head);
+                self.put(&head[..n]);
+                0
+            }
+        }
     }
 
     pub fn list_end(&mut self, index: usize) {
-        self.buffer.list_end(index);
+        match &mut self.sink {
+            Sink::Buffer => self.buffer.list_end(index),
+            Sink::Length(size) => *size += header_len(*size - index),
+            Sink::Counter(c) => {
+                let size = c.size - c.lists[index];
+                c.lists[index] = size;
+                c.size += header_len(size);
+            }
+            // The header was written by list.
+            Sink::Writer(_) => {}
+        }
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        match self.sink {
+            Sink::Buffer => self.buffer.write_bytes(bytes),
+            _ => self.put_bytes(bytes),
+        }
+    }
+
+    fn put_bytes(&mut self, bytes: &[u8]) {
+        if bytes.len() == 1 && bytes[0] <= 0x7F {
+            self.put(bytes);
+        } else {
+            self.put_string_header(bytes.len());
+            self.put(bytes);
+        }
+    }
+
+    pub fn write_uint(&mut self, i: u64) {
+        match self.sink {
+            Sink::Buffer => self.buffer.write_uint(i),
+            _ => self.put_bytes(trim_left_zeroes(&i.to_be_bytes())),
+        }
     }
 
-    pub fn write_bytes(&mut self, bytes: Vec<u8>) {
-        self.buffer.write_bytes(bytes);
+    pub fn write_bool(&mut self, b: bool) {
+        match self.sink {
+            Sink::Buffer => self.buffer.write_bool(b),
+            _ => self.put(&[if b { 0x01 } else { 0x80 }]),
+        }
+    }
+
+    pub fn write_big_int(&mut self, be_bytes: &[u8]) {
+        match self.sink {
+            Sink::Buffer => self.buffer.write_big_int(be_bytes),
+            _ => self.put_bytes(trim_left_zeroes(be_bytes)),
+        }
+    }
+
+    /// Returns the size of the encoding so far.
+    pub fn size(&self) -> usize {
+        match &self.sink {
+            Sink::Length(size) => *size,
+            Sink::Counter(c) => c.size,
+            _ => self.buffer.size(),
+        }
+    }
+
+    /// Returns the encoded bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.buffer.to_bytes()
     }
 
     /// It appends the encoded bytes to dst.
-    pub fn append_to_bytes(&self, dest: &mut Vec<u8>){
-        let size = self.buffer.size(); // Allocate the capacity of the array as size
-        
-        self.buffer.copy_to(dest);
+    pub fn append_to_bytes(&self, dest: &mut Vec<u8>) {
+        let start = dest.len();
+        dest.resize(start + self.buffer.size(), 0);
+        self.buffer.copy_to(&mut dest[start..]);
+    }
 
+    pub fn reset(&mut self) {
+        self.buffer.reset();
+        match &mut self.sink {
+            Sink::Length(size) => *size = 0,
+            Sink::Counter(c) => *c = Counter::default(),
+            _ => {}
+        }
     }
+}
+
+/// Returns the length of the RLP encoding of value, computed by a counting
+/// encoder which does not store the encoded data and does not allocate.
+pub(crate) fn count_encoded_len<T: super::Encodable + ?Sized>(value: &T) -> usize {
+    let mut rlp_enc = RlpEncoder::length();
+    value.encode(&mut rlp_enc);
+    rlp_enc.size()
+}
+
+/// Writes the RLP encoding of value to w without buffering it. The value is
+/// encoded twice: first to measure the sizes of all lists, then to write the
+/// encoding with the list headers in place. Since every item is written
+/// separately, w should be buffered if writes are expensive.
+pub fn encode_to<T: super::Encodable + ?Sized>(
+    value: &T,
+    w: &mut impl io::Write,
+) -> io::Result<()> {
+    let mut counter = RlpEncoder::counter();
+    value.encode(&mut counter);
+    let Sink::Counter(c) = counter.sink else {
+        unreachable!()
+    };
+
+    let mut rlp_enc = RlpEncoder::writer(w, c.lists);
+    value.encode(&mut rlp_enc);
+    match rlp_enc.sink {
+        Sink::Writer(Writer { err: Some(err), .. }) => Err(err),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rlp::{encode, Encodable, RlpItem};
+
+    /// Checks that encode_to and encoded_len agree with encode.
+    fn check<T: Encodable + ?Sized>(value: &T) {
+        let want = encode(value);
+        let mut out = Vec::new();
+        encode_to(value, &mut out).unwrap();
+        assert_eq!(out, want);
+        assert_eq!(value.encoded_len(), want.len());
+        assert_eq!(count_encoded_len(value), want.len());
+    }
+
+    #[test]
+    fn encode_to_matches_encode() {
+        check(&0u8);
+        check(&0x80u8);
+        check(&0xFFu8);
+        check(&1024u64);
+        check(&u128::MAX);
+        check(&true);
+        check("dog");
+        check(&[0x80u8][..]);
+        check(&vec![0x01u8; 1024]);
+        check(&vec![vec![1u64, 2, 3]; 30]);
+        check(&(1u64, "cat".to_string(), vec![vec![0u8; 60]]));
+        check(&RlpItem::List(vec![
+            RlpItem::List(Vec::new()),
+            RlpItem::List(vec![RlpItem::List(Vec::new()); 40]),
+            RlpItem::Bytes(vec![0xAA; 56]),
+        ]));
+    }
+
+    struct FailingWriter;
+
+    impl io::Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn encode_to_reports_write_errors() {
+        let err = encode_to(&vec![1u64, 2, 3], &mut FailingWriter).unwrap_err();
+        assert_eq!(err.to_string(), "full");
+    }
+
+    #[test]
+    fn buffer_and_reset() {
+        let mut rlp_enc = RlpEncoder::default();
+        let offset = rlp_enc.list();
+        rlp_enc.write_bytes(b"dog");
+        rlp_enc.write_uint(0);
+        rlp_enc.list_end(offset);
+        assert_eq!(rlp_enc.size(), 6);
+
+        let mut out = vec![0xFF];
+        rlp_enc.append_to_bytes(&mut out);
+        assert_eq!(out, [0xFF, 0xC5, 0x83, b'd', b'o', b'g', 0x80]);
 
-    pub fn reset(&self) {
-        unimplemented!()
+        rlp_enc.reset();
+        assert_eq!(rlp_enc.size(), 0);
+        assert!(rlp_enc.to_bytes().is_empty());
     }
 }
@@ -0,0 +1,677 @@
+//! A serde data format for RLP.
+//!
+//! Structs, tuples and sequences map to lists, and strings and byte buffers
+//! map to strings. Note that serde treats `Vec<u8>` and `[u8; N]` as
+//! sequences, so byte buffers must be serialized with `serialize_bytes`, e.g.
+//! through the `serde_bytes` crate, to encode as RLP strings. Unsigned
+//! integers are encoded as canonical big endian scalars, `None` as the empty
+//! string and newtype structs as their inner value.
+//!
+//! RLP has no representation for signed integers, floats, maps or enums, so
+//! these produce an `Error::UnsupportedType`.
+
+use std::fmt;
+
+use serde::{
+    de::{self, DeserializeSeed, SeqAccess, Visitor},
+    ser::{self, Impossible, Serialize},
+    Deserialize,
+};
+
+use super::{
+    decode::{split, split_list, split_string, Kind},
+    rlp_encoder::RlpEncoder,
+    traits::decode_in,
+    Decodable, DecodeError,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// The input is not valid RLP for the requested type.
+    Decode(DecodeError),
+    /// The type can't be represented in RLP.
+    UnsupportedType(&'static str),
+    /// A custom error raised by a Serialize or Deserialize implementation.
+    Message(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Decode(err) => err.fmt(f),
+            Error::UnsupportedType(ty) => write!(f, "rlp: {} is not supported", ty),
+            Error::Message(msg) => write!(f, "rlp: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<DecodeError> for Error {
+    fn from(err: DecodeError) -> Self {
+        Error::Decode(err)
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Message(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Message(msg.to_string())
+    }
+}
+
+/// Returns the RLP encoding of value.
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    let mut ser = Serializer::default();
+    value.serialize(&mut ser)?;
+
+    Ok(ser.rlp_enc.to_bytes())
+}
+
+/// Decodes a value from input, which must contain exactly one RLP item.
+pub fn from_slice<'de, T: Deserialize<'de>>(input: &'de [u8]) -> Result<T, Error> {
+    let mut de = Deserializer::new(input);
+    let value = T::deserialize(&mut de)?;
+    if !de.input.is_empty() {
+        return Err(DecodeError::TrailingBytes(de.offset()).into());
+    }
+
+    Ok(value)
+}
+
+/// Serializer writes values into an RlpEncoder.
+#[derive(Default)]
+pub struct Serializer {
+    pub rlp_enc: RlpEncoder<'static>,
+}
+
+/// ListSerializer writes the elements of a sequence, tuple or struct and
+/// closes the list when done.
+pub struct ListSerializer<'a> {
+    ser: &'a mut Serializer,
+    offset: usize,
+}
+
+impl<'a> ListSerializer<'a> {
+    fn new(ser: &'a mut Serializer) -> Self {
+        let offset = ser.rlp_enc.list();
+        Self { ser, offset }
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.ser.rlp_enc.list_end(self.offset);
+        Ok(())
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut Serializer {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = ListSerializer<'a>;
+    type SerializeTuple = ListSerializer<'a>;
+    type SerializeTupleStruct = ListSerializer<'a>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = ListSerializer<'a>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.rlp_enc.write_bool(v);
+        Ok(())
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<(), Error> {
+        Err(Error::UnsupportedType("signed integer"))
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<(), Error> {
+        Err(Error::UnsupportedType("signed integer"))
+    }
+
+    fn serialize_i32(self, _: i32) -> Result<(), Error> {
+        Err(Error::UnsupportedType("signed integer"))
+    }
+
+    fn serialize_i64(self, _: i64) -> Result<(), Error> {
+        Err(Error::UnsupportedType("signed integer"))
+    }
+
+    fn serialize_i128(self, _: i128) -> Result<(), Error> {
+        Err(Error::UnsupportedType("signed integer"))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        self.rlp_enc.write_uint(v);
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), Error> {
+        self.rlp_enc.write_big_int(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<(), Error> {
+        Err(Error::UnsupportedType("floating point number"))
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<(), Error> {
+        Err(Error::UnsupportedType("floating point number"))
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        self.rlp_enc.write_bytes(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.rlp_enc.write_bytes(&[]);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        ListSerializer::new(self).end()
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> Result<(), Error> {
+        Err(Error::UnsupportedType("enum"))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), Error> {
+        Err(Error::UnsupportedType("enum"))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<ListSerializer<'a>, Error> {
+        Ok(ListSerializer::new(self))
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<ListSerializer<'a>, Error> {
+        Ok(ListSerializer::new(self))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<ListSerializer<'a>, Error> {
+        Ok(ListSerializer::new(self))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(Error::UnsupportedType("enum"))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(Error::UnsupportedType("map"))
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<ListSerializer<'a>, Error> {
+        Ok(ListSerializer::new(self))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(Error::UnsupportedType("enum"))
+    }
+}
+
+impl ser::SerializeSeq for ListSerializer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        ListSerializer::end(self)
+    }
+}
+
+impl ser::SerializeTuple for ListSerializer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        ListSerializer::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for ListSerializer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        ListSerializer::end(self)
+    }
+}
+
+impl ser::SerializeStruct for ListSerializer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        ListSerializer::end(self)
+    }
+}
+
+/// Deserializer reads values from RLP input. Error offsets are relative to
+/// the start of the input it was created with.
+pub struct Deserializer<'de> {
+    input: &'de [u8], // unread part of outer
+    outer: &'de [u8],
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn new(input: &'de [u8]) -> Self {
+        Self {
+            input,
+            outer: input,
+        }
+    }
+
+    /// Returns the offset of the unread input in outer.
+    fn offset(&self) -> usize {
+        self.input.as_ptr() as usize - self.outer.as_ptr() as usize
+    }
+
+    fn decode<T: Decodable<'de>>(&mut self) -> Result<T, Error> {
+        Ok(decode_in(self.outer, &mut self.input)?)
+    }
+
+    fn next_string(&mut self) -> Result<&'de [u8], Error> {
+        let (content, rest) = split_string(self.input).map_err(|e| e.shift(self.offset()))?;
+        self.input = rest;
+        Ok(content)
+    }
+
+    fn next_str(&mut self) -> Result<&'de str, Error> {
+        let offset = self.offset();
+        let content = self.next_string()?;
+        std::str::from_utf8(content).map_err(|_| DecodeError::InvalidValue(offset).into())
+    }
+
+    /// Visits the content of the next list as a sequence, which must be read
+    /// completely.
+    fn list<V: Visitor<'de>>(&mut self, visitor: V) -> Result<V::Value, Error> {
+        let (content, rest) = split_list(self.input).map_err(|e| e.shift(self.offset()))?;
+        let mut list = Deserializer {
+            input: content,
+            outer: self.outer,
+        };
+        let value = visitor.visit_seq(&mut list)?;
+        if !list.input.is_empty() {
+            return Err(DecodeError::TrailingBytes(list.offset()).into());
+        }
+        self.input = rest;
+
+        Ok(value)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    /// RLP only distinguishes strings and lists, so strings are visited as
+    /// bytes and lists as sequences.
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let (kind, _, _) = split(self.input).map_err(|e| e.shift(self.offset()))?;
+        match kind {
+            Kind::Byte | Kind::String => visitor.visit_borrowed_bytes(self.next_string()?),
+            Kind::List => self.list(visitor),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bool(self.decode()?)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::UnsupportedType("signed integer"))
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::UnsupportedType("signed integer"))
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::UnsupportedType("signed integer"))
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::UnsupportedType("signed integer"))
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::UnsupportedType("signed integer"))
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u8(self.decode()?)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u16(self.decode()?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u32(self.decode()?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u64(self.decode()?)
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u128(self.decode()?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::UnsupportedType("floating point number"))
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::UnsupportedType("floating point number"))
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let offset = self.offset();
+        let mut chars = self.next_str()?.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(DecodeError::InvalidValue(offset).into()),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_str(self.next_str()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_bytes(self.next_string()?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    /// The empty string decodes as `None`, see the `Option` impl of
+    /// `Decodable`.
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let (kind, content, rest) = split(self.input).map_err(|e| e.shift(self.offset()))?;
+        if kind == Kind::String && content.is_empty() {
+            self.input = rest;
+            return visitor.visit_none();
+        }
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let offset = self.offset();
+        let (content, rest) = split_list(self.input).map_err(|e| e.shift(offset))?;
+        if !content.is_empty() {
+            return Err(DecodeError::TrailingBytes(offset + 1).into());
+        }
+        self.input = rest;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.list(visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _: usize, visitor: V) -> Result<V::Value, Error> {
+        self.list(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.list(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::UnsupportedType("map"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.list(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        _: V,
+    ) -> Result<V::Value, Error> {
+        Err(Error::UnsupportedType("enum"))
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::UnsupportedType("identifier"))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let (_, _, rest) = split(self.input).map_err(|e| e.shift(self.offset()))?;
+        self.input = rest;
+        visitor.visit_unit()
+    }
+}
+
+impl<'de> SeqAccess<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+        seed.deserialize(self).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        rlp::{self, RlpDecodable, RlpEncodable},
+        utils::to_hex,
+    };
+
+    #[derive(Debug, PartialEq, ::serde::Serialize, ::serde::Deserialize)]
+    struct Account<'a> {
+        nonce: u64,
+        #[serde(with = "serde_bytes")]
+        code: Vec<u8>,
+        name: &'a str,
+        slots: Vec<u32>,
+        owner: Option<u8>,
+    }
+
+    // The same struct with the derives of the rlp module.
+    #[derive(Debug, PartialEq, RlpEncodable, RlpDecodable)]
+    struct RlpAccount<'a> {
+        nonce: u64,
+        code: Vec<u8>,
+        name: &'a str,
+        slots: Vec<u32>,
+        owner: Option<u8>,
+    }
+
+    #[test]
+    fn round_trip() {
+        let account = Account {
+            nonce: 1024,
+            code: vec![0x60, 0x80],
+            name: "dog",
+            slots: vec![0, 1, 0x100],
+            owner: None,
+        };
+        let enc = to_vec(&account).unwrap();
+        assert_eq!(to_hex(&enc), "d182040082608083646f67c5800182010080");
+        assert_eq!(from_slice::<Account>(&enc).unwrap(), account);
+
+        let rlp_account = RlpAccount {
+            nonce: account.nonce,
+            code: account.code.clone(),
+            name: account.name,
+            slots: account.slots.clone(),
+            owner: account.owner,
+        };
+        assert_eq!(rlp::encode(&rlp_account), enc);
+        assert_eq!(rlp::decode::<RlpAccount>(&enc).unwrap(), rlp_account);
+    }
+
+    #[test]
+    fn scalars() {
+        assert_eq!(to_vec(&0u64).unwrap(), [0x80]);
+        assert_eq!(to_vec(&0x80u16).unwrap(), [0x81, 0x80]);
+        assert_eq!(to_vec(&true).unwrap(), [0x01]);
+        assert_eq!(to_vec(&Some(5u8)).unwrap(), [0x05]);
+        assert_eq!(to_vec(&()).unwrap(), [0xC0]);
+        assert_eq!(to_vec("dog").unwrap(), b"\x83dog");
+        assert_eq!(to_vec(&'a').unwrap(), b"a");
+        assert_eq!(from_slice::<u16>(&[0x81, 0x80]).unwrap(), 0x80);
+        assert_eq!(from_slice::<Option<u8>>(&[0x80]).unwrap(), None);
+        assert_eq!(from_slice::<char>(b"a").unwrap(), 'a');
+        assert_eq!(
+            from_slice::<(u8, String)>(b"\xc5\x01\x83dog").unwrap(),
+            (1, "dog".to_string())
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            to_vec(&-1i32),
+            Err(Error::UnsupportedType("signed integer"))
+        );
+        assert_eq!(
+            to_vec(&1.5f64),
+            Err(Error::UnsupportedType("floating point number"))
+        );
+        assert_eq!(
+            from_slice::<i8>(&[0x01]),
+            Err(Error::UnsupportedType("signed integer"))
+        );
+        assert_eq!(
+            from_slice::<u8>(&[0x01, 0x02]),
+            Err(Error::Decode(DecodeError::TrailingBytes(1)))
+        );
+        assert_eq!(
+            from_slice::<Vec<u8>>(&[0xC2, 0x01, 0x00]),
+            Err(Error::Decode(DecodeError::NonCanonicalInteger(2)))
+        );
+        assert_eq!(
+            from_slice::<(u8,)>(&[0xC2, 0x01, 0x02]),
+            Err(Error::Decode(DecodeError::TrailingBytes(2)))
+        );
+        assert_eq!(
+            from_slice::<char>(b"\x82ab"),
+            Err(Error::Decode(DecodeError::InvalidValue(0)))
+        );
+    }
+}
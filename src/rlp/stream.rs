@@ -0,0 +1,184 @@
+use super::{
+    decode::{split, Kind},
+    DecodeError,
+};
+
+/// RlpStream reads RLP values from a byte slice one at a time. It can be used
+/// to walk nested lists element by element, without splitting the input by
+/// hand.
+///
+/// The stream keeps track of the lists it has entered. Values in a list can
+/// only be read up to the end of that list, and the list must be consumed
+/// completely before it is left with `list_end`. Error offsets are relative
+/// to the start of the whole input.
+pub struct RlpStream<'a> {
+    buf: &'a [u8],
+    pos: usize,        // offset of the next value in buf
+    stack: Vec<usize>, // end offsets of the enclosing lists
+}
+
+impl<'a> RlpStream<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self {
+            buf,
+            pos: 0,
+            stack: Vec::new(),
+        }
+    }
+
+    /// Returns the kind and content size of the next value without consuming
+    /// it.
+    pub fn kind(&self) -> Result<(Kind, usize), DecodeError> {
+        let (kind, content, _) = self.peek()?;
+        Ok((kind, content.len()))
+    }
+
+    /// Reads the next value as a byte string. Single bytes below 0x80 are
+    /// returned as a one byte string.
+    pub fn bytes(&mut self) -> Result<&'a [u8], DecodeError> {
+        let (kind, content, size) = self.peek()?;
+        if kind == Kind::List {
+            return Err(DecodeError::UnexpectedList(self.pos));
+        }
+        self.pos += size;
+
+        Ok(content)
+    }
+
+    /// Reads the next value as an unsigned integer of at most 64 bits. The
+    /// encoding must be canonical, integers with leading zero bytes are
+    /// rejected.
+    pub fn uint(&mut self) -> Result<u64, DecodeError> {
+        let (kind, content, size) = self.peek()?;
+        if kind == Kind::List {
+            return Err(DecodeError::UnexpectedList(self.pos));
+        }
+        if content.len() > 8 {
+            return Err(DecodeError::ValueTooLarge(self.pos));
+        }
+        if content.first() == Some(&0) {
+            return Err(DecodeError::NonCanonicalInteger(self.pos));
+        }
+        self.pos += size;
+
+        Ok(content.iter().fold(0, |acc, &b| acc << 8 | u64::from(b)))
+    }
+
+    /// Returns the complete encoding of the next value, including its header.
+    pub fn raw(&mut self) -> Result<&'a [u8], DecodeError> {
+        let (_, _, size) = self.peek()?;
+        let raw = &self.buf[self.pos..self.pos + size];
+        self.pos += size;
+
+        Ok(raw)
+    }
+
+    /// Enters the list at the current position and returns the size of its
+    /// content. Subsequent reads return the values in the list.
+    pub fn list_start(&mut self) -> Result<usize, DecodeError> {
+        let (kind, content, size) = self.peek()?;
+        if kind != Kind::List {
+            return Err(DecodeError::UnexpectedString(self.pos));
+        }
+        let end = self.pos + size;
+        self.pos = end - content.len();
+        self.stack.push(end);
+
+        Ok(content.len())
+    }
+
+    /// Leaves the innermost list. It is an error to leave a list before all of
+    /// its values have been read.
+    pub fn list_end(&mut self) -> Result<(), DecodeError> {
+        if self.stack.is_empty() {
+            return Err(DecodeError::NotInList(self.pos));
+        }
+        if self.remaining() > 0 {
+            return Err(DecodeError::TrailingBytes(self.pos));
+        }
+        self.stack.pop();
+
+        Ok(())
+    }
+
+    /// Returns the number of unread bytes in the innermost list, or in the
+    /// whole input at the top level.
+    pub fn remaining(&self) -> usize {
+        self.end() - self.pos
+    }
+
+    /// Returns the number of lists the stream is currently in.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns the offset of the next value in the input.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    fn end(&self) -> usize {
+        self.stack.last().copied().unwrap_or(self.buf.len())
+    }
+
+    fn input(&self) -> &'a [u8] {
+        &self.buf[self.pos..self.end()]
+    }
+
+    /// Splits the next value off the input, which ends at the end of the
+    /// innermost list. Returns its kind, content and total encoded size.
+    fn peek(&self) -> Result<(Kind, &'a [u8], usize), DecodeError> {
+        let input = self.input();
+        let (kind, content, rest) = split(input).map_err(|e| e.shift(self.pos))?;
+
+        Ok((kind, content, input.len() - rest.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::from_hex;
+
+    #[test]
+    fn walk_nested_lists() {
+        // ["cat", [1024, []], 0]
+        let input = from_hex("ca83636174c4820400c080").unwrap();
+        let mut s = RlpStream::new(&input);
+        assert_eq!(s.kind().unwrap(), (Kind::List, 10));
+        assert_eq!(s.list_start().unwrap(), 10);
+        assert_eq!(s.bytes().unwrap(), b"cat");
+        assert_eq!(s.list_start().unwrap(), 4);
+        assert_eq!(s.depth(), 2);
+        assert_eq!(s.uint().unwrap(), 1024);
+        assert_eq!(s.raw().unwrap(), [0xC0]);
+        s.list_end().unwrap();
+        assert_eq!(s.kind().unwrap(), (Kind::String, 0));
+        assert_eq!(s.uint().unwrap(), 0);
+        s.list_end().unwrap();
+        assert_eq!(s.depth(), 0);
+        assert_eq!(s.remaining(), 0);
+        assert_eq!(s.position(), input.len());
+    }
+
+    #[test]
+    fn errors() {
+        let input = from_hex("c3820400").unwrap();
+        let mut s = RlpStream::new(&input);
+        assert_eq!(s.bytes(), Err(DecodeError::UnexpectedList(0)));
+        assert_eq!(s.list_end(), Err(DecodeError::NotInList(0)));
+        s.list_start().unwrap();
+        assert_eq!(s.list_start(), Err(DecodeError::UnexpectedString(1)));
+        assert_eq!(s.list_end(), Err(DecodeError::TrailingBytes(1)));
+
+        let input = from_hex("c48300000189010203040506070809").unwrap();
+        let mut s = RlpStream::new(&input);
+        s.list_start().unwrap();
+        assert_eq!(s.uint(), Err(DecodeError::NonCanonicalInteger(1)));
+        assert_eq!(s.bytes().unwrap(), [0, 0, 1]);
+        // Values can't be read past the end of the list.
+        assert_eq!(s.uint(), Err(DecodeError::InputTooShort(5)));
+        s.list_end().unwrap();
+        assert_eq!(s.uint(), Err(DecodeError::ValueTooLarge(5)));
+    }
+}
@@ -0,0 +1,646 @@
+use crate::utils::trim_left_zeroes;
+
+use super::{
+    decode::{split, split_list, split_string, Kind},
+    encode::header_len,
+    rlp_encoder::{count_encoded_len, RlpEncoder},
+    DecodeError,
+};
+
+/// A type that can be RLP encoded.
+pub trait Encodable {
+    /// Appends the RLP encoding of self to the encoder.
+    fn encode(&self, rlp_enc: &mut RlpEncoder);
+
+    /// Returns the length of the encoding of self. The default runs `encode`
+    /// on an encoder which only counts bytes, implementations can override it
+    /// to compute the length directly.
+    fn encoded_len(&self) -> usize {
+        count_encoded_len(self)
+    }
+
+    /// Encodes a slice of values. Slices encode as lists by default, `u8`
+    /// overrides this so that byte slices encode as strings.
+    fn encode_slice(items: &[Self], rlp_enc: &mut RlpEncoder)
+    where
+        Self: Sized,
+    {
+        let offset = rlp_enc.list();
+        for item in items {
+            item.encode(rlp_enc);
+        }
+        rlp_enc.list_end(offset);
+    }
+
+    /// Returns the encoded length of a slice, see `encode_slice`.
+    fn encoded_slice_len(items: &[Self]) -> usize
+    where
+        Self: Sized,
+    {
+        list_len(items.iter().map(Encodable::encoded_len).sum())
+    }
+}
+
+/// Returns the encoded length of a list with content of the given size.
+pub fn list_len(content: usize) -> usize {
+    header_len(content) + content
+}
+
+/// Returns the encoded length of a byte string with content of the given
+/// size, written with `RlpEncoder::write_string_header`.
+pub fn string_len(size: usize) -> usize {
+    header_len(size) + size
+}
+
+/// Returns the encoded length of the byte string b.
+pub fn bytes_len(b: &[u8]) -> usize {
+    if b.len() == 1 && b[0] <= 0x7F {
+        1
+    } else {
+        header_len(b.len()) + b.len()
+    }
+}
+
+/// A type that can be decoded from RLP. The lifetime is that of the input,
+/// which lets borrowed types like `&'a [u8]` point into it instead of
+/// copying.
+pub trait Decodable<'a>: Sized {
+    /// Decodes a value from the front of buf and advances buf past it. Error
+    /// offsets are relative to the start of buf.
+    fn decode(buf: &mut &'a [u8]) -> Result<Self, DecodeError>;
+
+    /// Decodes a vector of values. Vectors decode from lists by default, `u8`
+    /// overrides this so that byte vectors decode from strings.
+    fn decode_vec(buf: &mut &'a [u8]) -> Result<Vec<Self>, DecodeError> {
+        let outer = *buf;
+        let (mut content, rest) = split_list(buf)?;
+        let mut items = Vec::new();
+        while !content.is_empty() {
+            items.push(decode_in(outer, &mut content)?);
+        }
+        *buf = rest;
+
+        Ok(items)
+    }
+
+    /// Decodes a fixed size array of values, see `decode_vec`.
+    fn decode_array<const N: usize>(buf: &mut &'a [u8]) -> Result<[Self; N], DecodeError> {
+        Self::decode_vec(buf)?
+            .try_into()
+            .map_err(|_| DecodeError::InvalidValue(0))
+    }
+}
+
+/// Returns the RLP encoding of value.
+pub fn encode<T: Encodable + ?Sized>(value: &T) -> Vec<u8> {
+    let mut rlp_enc = RlpEncoder::default();
+    value.encode(&mut rlp_enc);
+
+    rlp_enc.to_bytes()
+}
+
+/// Decodes a value from buf, which must contain exactly one RLP item.
+pub fn decode<'a, T: Decodable<'a>>(input: &'a [u8]) -> Result<T, DecodeError> {
+    let mut buf = input;
+    let value = T::decode(&mut buf)?;
+    if !buf.is_empty() {
+        return Err(DecodeError::TrailingBytes(input.len() - buf.len()));
+    }
+
+    Ok(value)
+}
+
+/// Decodes a value from the front of buf, which is a part of outer such as
+/// the content of a list. Error offsets are reported relative to the start of
+/// outer instead of buf.
+pub fn decode_in<'a, T: Decodable<'a>>(
+    outer: &[u8],
+    buf: &mut &'a [u8],
+) -> Result<T, DecodeError> {
+    let offset = buf.as_ptr() as usize - outer.as_ptr() as usize;
+    T::decode(buf).map_err(|e| e.shift(offset))
+}
+
+/// Splits the next string item off buf and advances buf past it.
+fn next_string<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], DecodeError> {
+    let (content, rest) = split_string(buf)?;
+    *buf = rest;
+
+    Ok(content)
+}
+
+/// Decodes a canonical big-endian integer of at most size bytes, rejecting
+/// leading zero bytes.
+fn decode_uint(buf: &mut &[u8], size: usize) -> Result<u128, DecodeError> {
+    let content = next_string(buf)?;
+    if content.len() > size {
+        return Err(DecodeError::ValueTooLarge(0));
+    }
+    if content.first() == Some(&0) {
+        return Err(DecodeError::NonCanonicalInteger(0));
+    }
+
+    Ok(content.iter().fold(0, |acc, &b| acc << 8 | u128::from(b)))
+}
+
+macro_rules! impl_uint {
+    ($($t:ty),*) => {
+        $(
+            impl Encodable for $t {
+                fn encode(&self, rlp_enc: &mut RlpEncoder) {
+                    rlp_enc.write_big_int(&self.to_be_bytes());
+                }
+
+                fn encoded_len(&self) -> usize {
+                    bytes_len(trim_left_zeroes(&self.to_be_bytes()))
+                }
+            }
+
+            impl Decodable<'_> for $t {
+                fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+                    Ok(decode_uint(buf, std::mem::size_of::<$t>())? as $t)
+                }
+            }
+        )*
+    };
+}
+
+impl_uint!(u16, u32, u64, u128, usize);
+
+impl Encodable for u8 {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        rlp_enc.write_uint(u64::from(*self));
+    }
+
+    fn encoded_len(&self) -> usize {
+        bytes_len(trim_left_zeroes(&[*self]))
+    }
+
+    fn encode_slice(items: &[Self], rlp_enc: &mut RlpEncoder) {
+        rlp_enc.write_bytes(items);
+    }
+
+    fn encoded_slice_len(items: &[Self]) -> usize {
+        bytes_len(items)
+    }
+}
+
+impl Decodable<'_> for u8 {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        Ok(decode_uint(buf, 1)? as u8)
+    }
+
+    fn decode_vec(buf: &mut &[u8]) -> Result<Vec<Self>, DecodeError> {
+        Ok(next_string(buf)?.to_vec())
+    }
+
+    fn decode_array<const N: usize>(buf: &mut &[u8]) -> Result<[Self; N], DecodeError> {
+        let content = next_string(buf)?;
+        if content.len() > N {
+            return Err(DecodeError::ValueTooLarge(0));
+        }
+        content.try_into().map_err(|_| DecodeError::InvalidValue(0))
+    }
+}
+
+impl Encodable for bool {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        rlp_enc.write_bool(*self);
+    }
+
+    fn encoded_len(&self) -> usize {
+        1
+    }
+}
+
+impl Decodable<'_> for bool {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        match u8::decode(buf)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(DecodeError::InvalidValue(0)),
+        }
+    }
+}
+
+impl Encodable for str {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        rlp_enc.write_bytes(self.as_bytes());
+    }
+
+    fn encoded_len(&self) -> usize {
+        bytes_len(self.as_bytes())
+    }
+}
+
+impl Encodable for String {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        self.as_str().encode(rlp_enc);
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.as_str().encoded_len()
+    }
+}
+
+impl Decodable<'_> for String {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        String::from_utf8(next_string(buf)?.to_vec()).map_err(|_| DecodeError::InvalidValue(0))
+    }
+}
+
+/// Byte slices decode without copying, pointing into the input.
+impl<'de: 'a, 'a> Decodable<'de> for &'a [u8] {
+    fn decode(buf: &mut &'de [u8]) -> Result<Self, DecodeError> {
+        next_string(buf)
+    }
+}
+
+impl<'de: 'a, 'a> Decodable<'de> for &'a str {
+    fn decode(buf: &mut &'de [u8]) -> Result<Self, DecodeError> {
+        std::str::from_utf8(next_string(buf)?).map_err(|_| DecodeError::InvalidValue(0))
+    }
+}
+
+impl<T: Encodable> Encodable for [T] {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        T::encode_slice(self, rlp_enc);
+    }
+
+    fn encoded_len(&self) -> usize {
+        T::encoded_slice_len(self)
+    }
+}
+
+impl<T: Encodable, const N: usize> Encodable for [T; N] {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        T::encode_slice(self, rlp_enc);
+    }
+
+    fn encoded_len(&self) -> usize {
+        T::encoded_slice_len(self)
+    }
+}
+
+impl<'a, T: Decodable<'a>, const N: usize> Decodable<'a> for [T; N] {
+    fn decode(buf: &mut &'a [u8]) -> Result<Self, DecodeError> {
+        T::decode_array(buf)
+    }
+}
+
+impl<T: Encodable> Encodable for Vec<T> {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        T::encode_slice(self, rlp_enc);
+    }
+
+    fn encoded_len(&self) -> usize {
+        T::encoded_slice_len(self)
+    }
+}
+
+impl<'a, T: Decodable<'a>> Decodable<'a> for Vec<T> {
+    fn decode(buf: &mut &'a [u8]) -> Result<Self, DecodeError> {
+        T::decode_vec(buf)
+    }
+}
+
+impl<T: Encodable + ?Sized> Encodable for &T {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        (**self).encode(rlp_enc);
+    }
+
+    fn encoded_len(&self) -> usize {
+        (**self).encoded_len()
+    }
+}
+
+impl<T: Encodable + ?Sized> Encodable for Box<T> {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        (**self).encode(rlp_enc);
+    }
+
+    fn encoded_len(&self) -> usize {
+        (**self).encoded_len()
+    }
+}
+
+impl<'a, T: Decodable<'a>> Decodable<'a> for Box<T> {
+    fn decode(buf: &mut &'a [u8]) -> Result<Self, DecodeError> {
+        T::decode(buf).map(Box::new)
+    }
+}
+
+/// `None` encodes as the empty string. Note that `Some` of a value whose
+/// encoding is the empty string (such as zero) decodes as `None`.
+impl<T: Encodable> Encodable for Option<T> {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        match self {
+            Some(value) => value.encode(rlp_enc),
+            None => rlp_enc.write_bytes(&[]),
+        }
+    }
+
+    fn encoded_len(&self) -> usize {
+        match self {
+            Some(value) => value.encoded_len(),
+            None => 1,
+        }
+    }
+}
+
+impl<'a, T: Decodable<'a>> Decodable<'a> for Option<T> {
+    fn decode(buf: &mut &'a [u8]) -> Result<Self, DecodeError> {
+        let (kind, content, rest) = split(buf)?;
+        if kind == Kind::String && content.is_empty() {
+            *buf = rest;
+            return Ok(None);
+        }
+
+        T::decode(buf).map(Some)
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident),+) => {
+        /// Tuples encode as lists of their elements.
+        impl<$($name: Encodable),+> Encodable for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn encode(&self, rlp_enc: &mut RlpEncoder) {
+                let ($($name,)+) = self;
+                let offset = rlp_enc.list();
+                $($name.encode(rlp_enc);)+
+                rlp_enc.list_end(offset);
+            }
+
+            #[allow(non_snake_case)]
+            fn encoded_len(&self) -> usize {
+                let ($($name,)+) = self;
+                list_len(0 $(+ $name.encoded_len())+)
+            }
+        }
+
+        impl<'a, $($name: Decodable<'a>),+> Decodable<'a> for ($($name,)+) {
+            fn decode(buf: &mut &'a [u8]) -> Result<Self, DecodeError> {
+                let outer = *buf;
+                let (mut content, rest) = split_list(buf)?;
+                let value = ($(decode_in::<$name>(outer, &mut content)?,)+);
+                if !content.is_empty() {
+                    return Err(DecodeError::TrailingBytes(outer.len() - rest.len() - content.len()));
+                }
+                *buf = rest;
+
+                Ok(value)
+            }
+        }
+    };
+}
+
+impl_tuple!(A);
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
+impl_tuple!(A, B, C, D, E);
+impl_tuple!(A, B, C, D, E, F);
+impl_tuple!(A, B, C, D, E, F, G);
+impl_tuple!(A, B, C, D, E, F, G, H);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        rlp::{RlpDecodable, RlpEncodable},
+        utils::{from_hex, to_hex},
+    };
+
+    fn round_trip<T>(value: &T, want: &str)
+    where
+        T: Encodable + for<'a> Decodable<'a> + PartialEq + std::fmt::Debug,
+    {
+        let enc = encode(value);
+        assert_eq!(to_hex(&enc), want);
+        assert_eq!(value.encoded_len(), enc.len());
+        assert_eq!(&decode::<T>(&enc).unwrap(), value);
+    }
+
+    #[test]
+    fn integers() {
+        round_trip(&0u64, "80");
+        round_trip(&1u64, "01");
+        round_trip(&127u64, "7f");
+        round_trip(&128u64, "8180");
+        round_trip(&256u64, "820100");
+        round_trip(&0xFFFFFFu32, "83ffffff");
+        round_trip(&0x0102030405060708u64, "880102030405060708");
+        round_trip(&u128::MAX, "90ffffffffffffffffffffffffffffffff");
+        round_trip(&0u8, "80");
+        round_trip(&0x7Fu8, "7f");
+        round_trip(&0x80u8, "8180");
+        round_trip(&u8::MAX, "81ff");
+        round_trip(&true, "01");
+        round_trip(&false, "80");
+    }
+
+    #[test]
+    fn strings() {
+        round_trip(&String::new(), "80");
+        round_trip(&"dog".to_string(), "83646f67");
+        round_trip(&vec![0x7Fu8], "7f");
+        round_trip(&vec![0x80u8], "8180");
+        round_trip(&[1u8, 2, 3, 4], "8401020304");
+
+        let lorem = "Lorem ipsum dolor sit amet, consectetur adipisicing elit".to_string();
+        round_trip(&lorem, &format!("b838{}", to_hex(lorem.as_bytes())));
+    }
+
+    #[test]
+    fn lists() {
+        round_trip(&Vec::<u64>::new(), "c0");
+        round_trip(&vec![1u64, 2, 3], "c3010203");
+        round_trip(
+            &vec!["cat".to_string(), "dog".to_string()],
+            "c88363617483646f67",
+        );
+        round_trip(&(1u64, "dog".to_string(), vec![2u64]), "c70183646f67c102");
+        round_trip(&vec![Vec::<Vec<u64>>::new(); 2], "c2c0c0");
+        round_trip(&Some(5u64), "05");
+        round_trip(&None::<u64>, "80");
+
+        // The set theoretical representation of three.
+        type Set = Vec<u64>;
+        let three: (Set, (Set,), (Set, (Set,))) =
+            (Vec::new(), (Vec::new(),), (Vec::new(), (Vec::new(),)));
+        assert_eq!(to_hex(&encode(&three)), "c7c0c1c0c3c0c1c0");
+    }
+
+    #[test]
+    fn decode_errors() {
+        let decode_hex = |hex: &str| decode::<u64>(&from_hex(hex).unwrap());
+        assert_eq!(decode_hex("8100"), Err(DecodeError::NonCanonicalSize(0)));
+        assert_eq!(
+            decode_hex("820001"),
+            Err(DecodeError::NonCanonicalInteger(0))
+        );
+        assert_eq!(
+            decode_hex("89010203040506070809"),
+            Err(DecodeError::ValueTooLarge(0))
+        );
+        assert_eq!(decode_hex("c0"), Err(DecodeError::UnexpectedList(0)));
+        assert_eq!(decode_hex("83"), Err(DecodeError::InputTooShort(0)));
+        assert_eq!(decode_hex("0102"), Err(DecodeError::TrailingBytes(1)));
+        assert_eq!(decode_hex(""), Err(DecodeError::InputTooShort(0)));
+
+        assert_eq!(decode::<bool>(&[0x02]), Err(DecodeError::InvalidValue(0)));
+        assert_eq!(
+            decode::<String>(&[0x81, 0xFF]),
+            Err(DecodeError::InvalidValue(0))
+        );
+        assert_eq!(
+            decode::<Vec<u64>>(&[0x83, 1, 2, 3]),
+            Err(DecodeError::UnexpectedString(0))
+        );
+
+        // Errors inside lists report offsets into the whole input.
+        assert_eq!(
+            decode::<Vec<u64>>(&from_hex("c401820001").unwrap()),
+            Err(DecodeError::NonCanonicalInteger(2))
+        );
+        assert_eq!(
+            decode::<(u64, u64)>(&from_hex("c3010203").unwrap()),
+            Err(DecodeError::TrailingBytes(3))
+        );
+    }
+
+    #[test]
+    fn decode_borrowed() {
+        let enc = from_hex("c883646f678301020f").unwrap();
+        let (name, data): (&str, &[u8]) = decode(&enc).unwrap();
+        assert_eq!(name, "dog");
+        assert_eq!(data, &enc[6..]);
+    }
+
+    #[derive(Debug, PartialEq, RlpEncodable, RlpDecodable)]
+    struct Plain {
+        a: u64,
+        b: Vec<u8>,
+        #[rlp(skip)]
+        cache: u32,
+        c: Vec<u64>,
+    }
+
+    #[derive(Debug, PartialEq, RlpEncodable, RlpDecodable)]
+    #[rlp(transparent)]
+    struct Wrapper([u8; 4]);
+
+    #[derive(Debug, Default, PartialEq, RlpEncodable, RlpDecodable)]
+    #[rlp(trailing)]
+    struct Trailing {
+        a: u64,
+        b: Option<u64>,
+        c: Option<[u8; 2]>,
+        d: Option<Vec<u8>>,
+    }
+
+    #[derive(Debug, PartialEq, RlpDecodable)]
+    struct Borrowed<'a> {
+        name: &'a str,
+        data: &'a [u8],
+    }
+
+    #[test]
+    fn derive_struct() {
+        let plain = Plain {
+            a: 1024,
+            b: b"dog".to_vec(),
+            cache: 0,
+            c: vec![1, 2],
+        };
+        round_trip(&plain, "ca82040083646f67c20102");
+
+        // Skipped fields decode to their default.
+        let mut cached = plain;
+        cached.cache = 7;
+        let decoded: Plain = decode(&encode(&cached)).unwrap();
+        assert_eq!(decoded.cache, 0);
+
+        round_trip(&Wrapper(*b"cat!"), "8463617421");
+    }
+
+    #[test]
+    fn derive_borrowed() {
+        let enc = from_hex("c883646f678301020f").unwrap();
+        let decoded: Borrowed = decode(&enc).unwrap();
+        assert_eq!(decoded.name, "dog");
+        assert_eq!(decoded.data, [1, 2, 15]);
+    }
+
+    #[test]
+    fn derive_trailing() {
+        // Unset trailing fields are omitted.
+        round_trip(&Trailing::default(), "c180");
+        round_trip(
+            &Trailing {
+                a: 1,
+                b: Some(2),
+                ..Default::default()
+            },
+            "c20102",
+        );
+
+        // Set fields holding zero values are kept.
+        round_trip(
+            &Trailing {
+                b: Some(0),
+                ..Default::default()
+            },
+            "c28080",
+        );
+        round_trip(
+            &Trailing {
+                b: Some(0),
+                c: Some([0, 0]),
+                d: Some(Vec::new()),
+                ..Default::default()
+            },
+            "c6808082000080",
+        );
+
+        // Unset fields before a set one are encoded as their zero value,
+        // and decode as set.
+        let gap = Trailing {
+            d: Some(vec![9]),
+            ..Default::default()
+        };
+        let enc = encode(&gap);
+        assert_eq!(to_hex(&enc), "c6808082000009");
+        assert_eq!(gap.encoded_len(), enc.len());
+        assert_eq!(
+            decode::<Trailing>(&enc).unwrap(),
+            Trailing {
+                b: Some(0),
+                c: Some([0, 0]),
+                d: Some(vec![9]),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn derive_errors() {
+        // The list must contain exactly the fields.
+        assert_eq!(
+            decode::<Plain>(&from_hex("c50183646f67").unwrap()),
+            Err(DecodeError::InputTooShort(6))
+        );
+        assert_eq!(
+            decode::<Trailing>(&from_hex("c50102820000").unwrap()),
+            Ok(Trailing {
+                a: 1,
+                b: Some(2),
+                c: Some([0, 0]),
+                d: None,
+            })
+        );
+        assert!(decode::<Trailing>(&from_hex("c70102820000800a").unwrap()).is_err());
+        assert!(decode::<Trailing>(&from_hex("c0").unwrap()).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use crate::utils::bytes_to_hash;
+
+use super::{
+    encoding::hex_to_compact,
+    hash::Hasher,
+    node::{FullNode, Node},
+    types::{Hash, NodeSet, TrieNode, EMPTY_ROOT_HASH},
+    Trie,
+};
+
+/// Committer is the tool used for the trie Commit operation. It collects the
+/// dirty nodes of the trie into a node set, replacing them with their hashes.
+struct Committer<'a> {
+    nodes: &'a mut NodeSet,
+    hasher: Hasher,
+}
+
+impl Committer<'_> {
+    /// Collapses a node down into a hash node and collects it if dirty.
+    fn commit(&mut self, path: Vec<u8>, node: &Node) -> Node {
+        match node {
+            Node::ShortNode(n) => {
+                // if this path is clean, use available cached data
+                if let (Some(hash), false) = n.cache() {
+                    return Node::HashNode(hash);
+                }
+                let mut collapsed = n.clone();
+                // If the child is fullNode, recursively commit,
+                // otherwise it can only be hashNode or valueNode.
+                if let Node::FullNode(_) = n.val.as_ref() {
+                    let child_path = [path.as_slice(), &n.key].concat();
+                    collapsed.val = Box::new(self.commit(child_path, &n.val));
+                }
+                collapsed.key = hex_to_compact(&n.key);
+                self.store(path, Node::ShortNode(collapsed))
+            }
+            Node::FullNode(n) => {
+                if let (Some(hash), false) = n.cache() {
+                    return Node::HashNode(hash);
+                }
+                let collapsed = FullNode {
+                    children: self.commit_children(&path, n),
+                    flags: n.flags.clone(),
+                };
+                self.store(path, Node::FullNode(collapsed))
+            }
+            _ => node.clone(),
+        }
+    }
+
+    /// Commits the children of the given full node.
+    fn commit_children(&mut self, path: &[u8], node: &FullNode) -> Vec<Node> {
+        let mut children = node.children.clone();
+        for (i, child) in node.children.iter().enumerate().take(16) {
+            match child {
+                Node::Empty | Node::HashNode(_) => {}
+                _ => children[i] = self.commit([path, &[i as u8]].concat(), child),
+            }
+        }
+        children
+    }
+
+    /// Stores the node in the node set and returns its hash, unless the node
+    /// is embedded in its parent, in which case it is returned as is.
+    fn store(&mut self, path: Vec<u8>, node: Node) -> Node {
+        let hash = match &node {
+            Node::FullNode(n) => n.flags.hash.clone(),
+            Node::ShortNode(n) => n.flags.hash.clone(),
+            _ => None,
+        };
+        // This was not generated - must be a small node stored in the parent.
+        let Some(hash) = hash else {
+            return node;
+        };
+
+        let blob = self.hasher.node_to_bytes(&node);
+        self.nodes.nodes.insert(
+            path,
+            TrieNode {
+                hash: bytes_to_hash(&hash),
+                blob,
+            },
+        );
+        Node::HashNode(hash)
+    }
+}
+
+impl Trie {
+    /// Collects all dirty nodes in the trie and replaces them with the
+    /// corresponding node hash. All collected nodes are returned in a node
+    /// set keyed by their path, along with the root hash. The node set is
+    /// `None` if the trie is clean.
+    ///
+    /// Once the trie is committed, it's not usable anymore. A new trie must
+    /// be created with the new root and an updated database.
+    pub fn commit(&mut self) -> (Hash, Option<NodeSet>) {
+        self.committed = Some(true);
+
+        if self.root.is_none() {
+            return (EMPTY_ROOT_HASH, None);
+        }
+        let root_hash = self.hash();
+        let root = self.root.take().unwrap_or(Node::Empty);
+
+        // Do a quick check if we really need to commit. This can happen
+        // e.g. if we load a trie for reading storage values, but don't
+        // write to it.
+        let dirty = match &root {
+            Node::FullNode(n) => n.flags.dirty,
+            Node::ShortNode(n) => n.flags.dirty,
+            _ => false,
+        };
+        if !dirty {
+            self.root = Some(Node::HashNode(root_hash.to_vec()));
+            return (root_hash, None);
+        }
+
+        let mut nodes = NodeSet {
+            owner: self.owner,
+            ..Default::default()
+        };
+        let mut committer = Committer {
+            nodes: &mut nodes,
+            hasher: Hasher::new(),
+        };
+        self.root = Some(committer.commit(Vec::new(), &root));
+
+        (root_hash, Some(nodes))
+    }
+}
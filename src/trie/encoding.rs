@@ -1,4 +1,4 @@
-pub fn prefix_len(a: &Vec<u8>, b: &Vec<u8>) -> usize {
+pub fn prefix_len(a: &[u8], b: &[u8]) -> usize {
     let length = if a.len() > b.len() { b.len() } else { a.len() };
     let mut i = 0;
 
@@ -7,7 +7,7 @@ pub fn prefix_len(a: &Vec<u8>, b: &Vec<u8>) -> usize {
             break;
         }
 
-        i = i + 1;
+        i += 1;
     }
 
     i
@@ -53,7 +53,7 @@ pub fn compact_to_hex(compact: &[u8]) -> Vec<u8> {
     base[chop as usize..].to_vec()
 }
 
-fn keybytes_to_hex(str: &[u8]) -> Vec<u8> {
+pub fn keybytes_to_hex(str: &[u8]) -> Vec<u8> {
     let l = str.len() * 2 + 1;
     let mut nibbles = vec![0u8; l];
     
@@ -73,5 +73,16 @@ fn decode_nibbles(nibbles: &[u8], bytes: &mut [u8]) {
 }
 
 pub fn has_term(s: &[u8]) -> bool {
-    s.last().map_or(false, |&b| b == 16)
+    s.last() == Some(&16)
+}
+
+pub fn hex_to_keybytes(hex: &[u8]) -> Vec<u8> {
+    let hex = if has_term(hex) { &hex[..hex.len() - 1] } else { hex };
+    if hex.len() & 1 != 0 {
+        panic!("can't convert hex key of odd length");
+    }
+
+    let mut key = vec![0u8; hex.len() / 2];
+    decode_nibbles(hex, &mut key);
+    key
 }
@@ -1,22 +1,23 @@
-use std::io::Write;
+use sha3::{Digest, Keccak256};
 
-use crate::{rlp::rlp_encoder::RlpEncoder, trie::encoding::hex_to_compact};
+use crate::{
+    rlp::{encode_to, rlp_encoder::RlpEncoder, Encodable},
+    trie::encoding::hex_to_compact,
+    utils::keccak256,
+};
 
-use super::node::{self, FullNode, HashNode, Node, ShortNode};
-use sha3::{self, Digest, Sha3_256};
+use super::node::{FullNode, HashNode, Node, ShortNode};
+
+#[derive(Default)]
 pub struct Hasher {
-    rlp_enc: RlpEncoder,
+    sha: Keccak256,
+    rlp_enc: RlpEncoder<'static>,
     temp: Vec<u8>,
 }
 
 impl Hasher {
     pub fn new() -> Self {
-        Self {
-            rlp_enc: RlpEncoder {
-                ..Default::default()
-            },
-            temp: Vec::new(),
-        }
+        Self::default()
     }
 
     /// Collapses a node into a hash node and prepares its replacement.
@@ -33,30 +34,40 @@ impl Hasher {
     /// * `node` - A reference to the node to be collapsed. The exact type depends on your trie implementation.
     ///
     pub fn hash(&mut self, node: &Node, force: bool) -> (Node, Node) {
+        // Return the cached hash if it's available
+        let cached_hash = match node {
+            Node::FullNode(n) => n.flags.hash.clone(),
+            Node::ShortNode(n) => n.flags.hash.clone(),
+            _ => None,
+        };
+        if let Some(hash) = cached_hash {
+            return (Node::HashNode(hash), node.clone());
+        }
+
         match node {
             Node::FullNode(n) => {
-                let (collapsed, mut cached) = self.hash_full_node_children(&n);
+                let (collapsed, mut cached) = self.hash_full_node_children(n);
                 let hashed = self.full_node_to_hash(&collapsed, force);
                 if let Node::HashNode(hn) = &hashed {
                     cached.flags.hash = Some(hn.clone());
                 } else {
                     cached.flags.hash = None;
                 }
-                return (hashed, Node::FullNode(cached));
+                (hashed, Node::FullNode(cached))
             }
             Node::ShortNode(n) => {
-                let (collapsed, mut cached) = self.hash_short_node_children(&n);
+                let (collapsed, mut cached) = self.hash_short_node_children(n);
                 let hashed = self.short_node_to_hash(&collapsed, force);
                 if let Node::HashNode(hn) = &hashed {
                     cached.flags.hash = Some(hn.clone());
                 } else {
                     cached.flags.hash = None;
                 }
-                return (hashed, Node::ShortNode(cached));
+                (hashed, Node::ShortNode(cached))
             }
             _ => {
                 // Value and hash nodes don't have children, so they're left as were
-                return (node.clone(), node.clone());
+                (node.clone(), node.clone())
             }
         }
     }
@@ -108,17 +119,15 @@ impl Hasher {
     ///
     /// # Returns
     ///
-    /// - `Some(HashNode)` if the RLP data is 32 bytes or larger.
-    /// - `None` if the RLP data is smaller than 32 bytes.
+    /// - A `HashNode` if the RLP data is 32 bytes or larger, or `force` is set.
+    /// - The node itself if the RLP data is smaller than 32 bytes.
     pub fn short_node_to_hash(&mut self, node: &ShortNode, force: bool) -> Node {
-        node.encode(&mut self.rlp_enc);
-        let enc = self.encode_bytes();
-
-        if self.temp.len() > 32 && !force {
+        if node.encoded_len() < 32 && !force {
+            // Nodes smaller than 32 bytes are stored inside their parent
             return Node::ShortNode(node.clone());
         }
 
-        Node::HashNode(self.hash_data(&self.temp))
+        Node::HashNode(self.hash_node(node))
     }
 
     /// Creates a `HashNode` from a `FullNode`.
@@ -140,14 +149,11 @@ impl Hasher {
     /// The exact hashing mechanism and handling of `None` values should be
     /// implemented according to the specific requirements of your trie structure.
     pub fn full_node_to_hash(&mut self, node: &FullNode, force: bool) -> Node {
-        node.encode(&mut self.rlp_enc);
-        let enc = self.encode_bytes();
-
-        if self.temp.len() > 32 && !force {
+        if node.encoded_len() < 32 && !force {
             return Node::FullNode(node.clone());
         }
 
-        Node::HashNode(self.hash_data(&self.temp))
+        Node::HashNode(self.hash_node(node))
     }
 
     /// Returns the result of the last encoding operation on `self.rlp_enc`.
@@ -155,7 +161,7 @@ impl Hasher {
     ///
     /// All node encoding must be done like this:
     ///
-    /// ```
+    /// ```ignore
     /// node.encode(&mut self.rlp_enc);
     /// let enc = self.encoded_bytes();
     /// ```
@@ -163,23 +169,47 @@ impl Hasher {
     /// This convention exists because `node.encode` can only be inlined/escape-analyzed when
     /// called on a concrete receiver type.
     pub fn encode_bytes(&mut self) -> &[u8] {
+        self.temp.clear();
         self.rlp_enc.append_to_bytes(&mut self.temp);
         self.rlp_enc.reset();
 
-        return &self.temp;
+        &self.temp
     }
 
     /// Hashes the provided data.
-    pub fn hash_data(&self, data: &Vec<u8>) -> HashNode {
-        //FIXME:
-        let mut hasher = Sha3_256::new();
-        hasher.update(data);
+    pub fn hash_data(&self, data: &[u8]) -> HashNode {
+        keccak256(data).to_vec()
+    }
 
-        // read hash digest
-        let result = hasher.finalize();
+    /// Hashes the RLP encoding of a node. The encoding is written straight
+    /// into the hasher, without collecting it in a buffer first.
+    fn hash_node(&mut self, node: &impl Encodable) -> HashNode {
+        encode_to(node, &mut self.sha).expect("writing to a hasher can't fail");
+        self.sha.finalize_reset().to_vec()
+    }
 
-        let hash_node = result.to_vec();
+    /// Used to construct trie proofs. Returns the collapsed node (for later RLP
+    /// encoding) as well as the hashed node, unless the node is smaller than 32
+    /// bytes, in which case it is returned as is.
+    pub fn proof_hash(&mut self, original: &Node) -> (Node, Node) {
+        match original {
+            Node::ShortNode(n) => {
+                let (collapsed, _) = self.hash_short_node_children(n);
+                let hashed = self.short_node_to_hash(&collapsed, false);
+                (Node::ShortNode(collapsed), hashed)
+            }
+            Node::FullNode(n) => {
+                let (collapsed, _) = self.hash_full_node_children(n);
+                let hashed = self.full_node_to_hash(&collapsed, false);
+                (Node::FullNode(collapsed), hashed)
+            }
+            _ => (original.clone(), original.clone()),
+        }
+    }
 
-        hash_node
+    /// Returns the RLP encoding of a collapsed node.
+    pub fn node_to_bytes(&mut self, node: &Node) -> Vec<u8> {
+        node.encode(&mut self.rlp_enc);
+        self.encode_bytes().to_vec()
     }
 }
@@ -0,0 +1,507 @@
+use std::fmt;
+
+use crate::{
+    rlp::{
+        bytes_len,
+        decode::{count_values, split_list, split_string},
+        Encodable,
+    },
+    utils::{bytes_to_hash, to_hex},
+};
+
+use super::{
+    encoding::hex_to_keybytes,
+    hash::Hasher,
+    node::{decode_node, Node},
+    trie_reader::{new_trie_reader, TrieReader},
+    types::{Database, Hash, EMPTY_ROOT_HASH, HASH_LENGTH},
+};
+
+/// Statistics gathered while walking a single trie.
+#[derive(Default, Debug, Clone)]
+pub struct TrieStats {
+    pub full_nodes: usize,
+    pub short_nodes: usize,
+    pub value_nodes: usize,
+    /// Nodes referenced by hash and loaded from the database, including the root.
+    pub hash_nodes: usize,
+
+    /// Values held by short nodes or in the value slot of full nodes, i.e. the
+    /// number of keys in the trie.
+    pub leaves: usize,
+    /// `leaf_depths[d]` is the number of leaves found `d` nodes below the root.
+    pub leaf_depths: Vec<usize>,
+
+    /// Total RLP encoded size of all nodes of each type.
+    pub full_size: usize,
+    pub short_size: usize,
+    pub value_size: usize,
+
+    /// Children of full and short nodes stored inline in their parent.
+    pub embedded_children: usize,
+    /// Children of full and short nodes stored separately and referenced by hash.
+    pub hashed_children: usize,
+}
+
+impl TrieStats {
+    pub fn avg_full_size(&self) -> f64 {
+        average(self.full_size, self.full_nodes)
+    }
+
+    pub fn avg_short_size(&self) -> f64 {
+        average(self.short_size, self.short_nodes)
+    }
+
+    pub fn avg_value_size(&self) -> f64 {
+        average(self.value_size, self.value_nodes)
+    }
+
+    pub fn total_size(&self) -> usize {
+        self.full_size + self.short_size + self.value_size
+    }
+
+    fn add_leaf(&mut self, depth: usize) {
+        self.leaves += 1;
+        if self.leaf_depths.len() <= depth {
+            self.leaf_depths.resize(depth + 1, 0);
+        }
+        self.leaf_depths[depth] += 1;
+    }
+}
+
+fn average(total: usize, count: usize) -> f64 {
+    if count == 0 {
+        return 0.0;
+    }
+    total as f64 / count as f64
+}
+
+impl fmt::Display for TrieStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<8} {:>10} {:>14} {:>10}",
+            "type", "count", "size", "avg"
+        )?;
+        writeln!(
+            f,
+            "{:<8} {:>10} {:>14} {:>10.2}",
+            "full",
+            self.full_nodes,
+            self.full_size,
+            self.avg_full_size()
+        )?;
+        writeln!(
+            f,
+            "{:<8} {:>10} {:>14} {:>10.2}",
+            "short",
+            self.short_nodes,
+            self.short_size,
+            self.avg_short_size()
+        )?;
+        writeln!(
+            f,
+            "{:<8} {:>10} {:>14} {:>10.2}",
+            "value",
+            self.value_nodes,
+            self.value_size,
+            self.avg_value_size()
+        )?;
+        writeln!(f, "{:<8} {:>10}", "hash", self.hash_nodes)?;
+        writeln!(f, "leaves: {}", self.leaves)?;
+        writeln!(
+            f,
+            "children: {} embedded, {} hashed",
+            self.embedded_children, self.hashed_children
+        )?;
+        writeln!(f, "leaf depths:")?;
+        for (depth, count) in self.leaf_depths.iter().enumerate() {
+            if *count > 0 {
+                writeln!(f, "  {:>3}: {}", depth, count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Statistics of a state trie and the storage tries of its accounts.
+#[derive(Default, Debug, Clone)]
+pub struct StateStats {
+    pub accounts: TrieStats,
+    /// Number of accounts with a non-empty storage trie.
+    pub storage_tries: usize,
+    /// The largest storage tries by number of slots, keyed by owner (the hash
+    /// of the account address), largest first.
+    pub largest_storage: Vec<(Hash, TrieStats)>,
+}
+
+impl fmt::Display for StateStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "account trie:")?;
+        write!(f, "{}", self.accounts)?;
+        writeln!(f, "storage tries: {}", self.storage_tries)?;
+        for (owner, stats) in &self.largest_storage {
+            writeln!(
+                f,
+                "  {}: {} slots, {} nodes, {} bytes",
+                to_hex(owner),
+                stats.leaves,
+                stats.full_nodes + stats.short_nodes,
+                stats.total_size()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Walks the trie with the given root through a `TrieReader` and collects
+/// node statistics. The root must be available in `db`.
+pub fn inspect(root: Hash, db: &impl Database) -> Result<TrieStats, ()> {
+    let reader = new_trie_reader(&root, &[0; HASH_LENGTH], db).map_err(|_| ())?;
+    let mut stats = TrieStats::default();
+    Inspector {
+        reader: &reader,
+        stats: &mut stats,
+        accounts: None,
+    }
+    .walk_root(root)?;
+
+    Ok(stats)
+}
+
+/// Walks the state trie with the given root together with the storage trie
+/// of every account, keeping the `top` largest storage tries.
+pub fn inspect_state(root: Hash, db: &impl Database, top: usize) -> Result<StateStats, ()> {
+    let reader = new_trie_reader(&root, &[0; HASH_LENGTH], db).map_err(|_| ())?;
+    let mut stats = TrieStats::default();
+    let mut accounts = Vec::new();
+    Inspector {
+        reader: &reader,
+        stats: &mut stats,
+        accounts: Some(&mut accounts),
+    }
+    .walk_root(root)?;
+
+    let mut state = StateStats {
+        accounts: stats,
+        ..Default::default()
+    };
+    for (owner, storage_root) in accounts {
+        let reader = new_trie_reader(&root, &owner, db).map_err(|_| ())?;
+        let mut stats = TrieStats::default();
+        Inspector {
+            reader: &reader,
+            stats: &mut stats,
+            accounts: None,
+        }
+        .walk_root(storage_root)?;
+
+        state.storage_tries += 1;
+        state.largest_storage.push((owner, stats));
+    }
+    state
+        .largest_storage
+        .sort_by(|a, b| b.1.leaves.cmp(&a.1.leaves).then(a.0.cmp(&b.0)));
+    state.largest_storage.truncate(top);
+
+    Ok(state)
+}
+
+struct Inspector<'a> {
+    reader: &'a TrieReader,
+    stats: &'a mut TrieStats,
+    /// When walking a state trie, collects (owner, storage root) of every
+    /// account with a non-empty storage trie.
+    accounts: Option<&'a mut Vec<(Hash, Hash)>>,
+}
+
+impl Inspector<'_> {
+    fn walk_root(&mut self, root: Hash) -> Result<(), ()> {
+        if root == [0; HASH_LENGTH] || root == EMPTY_ROOT_HASH {
+            return Ok(());
+        }
+        let node = self.load(root.to_vec(), None)?;
+        self.walk(&node, Vec::new(), 0)
+    }
+
+    /// Resolves a hash node through the reader, accounting for its size.
+    fn load(&mut self, hash: Vec<u8>, path: Option<Vec<u8>>) -> Result<Node, ()> {
+        let blob = self.reader.node(path, bytes_to_hash(&hash))?;
+        let node = decode_node(Some(hash), &blob)?;
+
+        self.stats.hash_nodes += 1;
+        match &node {
+            Node::FullNode(_) => self.stats.full_size += blob.len(),
+            Node::ShortNode(_) => self.stats.short_size += blob.len(),
+            _ => {}
+        }
+        Ok(node)
+    }
+
+    fn walk(&mut self, node: &Node, path: Vec<u8>, depth: usize) -> Result<(), ()> {
+        match node {
+            Node::FullNode(n) => {
+                self.stats.full_nodes += 1;
+                for (i, child) in n.children.iter().enumerate() {
+                    let child_path = [path.as_slice(), &[i as u8]].concat();
+                    // The value of a key which is a prefix of other keys is
+                    // stored in the last slot.
+                    if let Node::ValueNode(v) = child {
+                        self.stats.add_leaf(depth);
+                        self.visit_value(&child_path, v);
+                    }
+                    self.walk_child(child, child_path, depth + 1)?;
+                }
+            }
+            Node::ShortNode(n) => {
+                self.stats.short_nodes += 1;
+                let child_path = [path.as_slice(), &n.key].concat();
+                if let Node::ValueNode(v) = n.val.as_ref() {
+                    self.stats.add_leaf(depth);
+                    self.visit_value(&child_path, v);
+                }
+                self.walk_child(&n.val, child_path, depth + 1)?;
+            }
+            Node::ValueNode(v) => {
+                self.stats.value_nodes += 1;
+                self.stats.value_size += bytes_len(v);
+            }
+            Node::HashNode(_) | Node::Empty => {}
+        }
+        Ok(())
+    }
+
+    fn walk_child(&mut self, child: &Node, path: Vec<u8>, depth: usize) -> Result<(), ()> {
+        match child {
+            Node::HashNode(hash) => {
+                self.stats.hashed_children += 1;
+                let node = self.load(hash.clone(), Some(path.clone()))?;
+                self.walk(&node, path, depth)
+            }
+            Node::FullNode(_) | Node::ShortNode(_) => {
+                self.stats.embedded_children += 1;
+                // Collapsing the node brings the keys of its short nodes into
+                // compact form, as they are encoded in the parent.
+                let (collapsed, _) = Hasher::new().hash(child, false);
+                match child {
+                    Node::FullNode(_) => self.stats.full_size += collapsed.encoded_len(),
+                    _ => self.stats.short_size += collapsed.encoded_len(),
+                }
+                self.walk(child, path, depth)
+            }
+            _ => self.walk(child, path, depth),
+        }
+    }
+
+    /// Records the storage trie of an account leaf when walking a state trie.
+    fn visit_value(&mut self, path: &[u8], value: &[u8]) {
+        let Some(accounts) = self.accounts.as_mut() else {
+            return;
+        };
+        if let Some(storage_root) = account_storage_root(value) {
+            if storage_root != EMPTY_ROOT_HASH {
+                accounts.push((bytes_to_hash(&hex_to_keybytes(path)), storage_root));
+            }
+        }
+    }
+}
+
+/// Extracts the storage root from a consensus encoded account
+/// `[nonce, balance, storage_root, code_hash]`.
+fn account_storage_root(value: &[u8]) -> Option<Hash> {
+    let (elems, _) = split_list(value).ok()?;
+    if count_values(elems).ok()? != 4 {
+        return None;
+    }
+    let (_, rest) = split_string(elems).ok()?;
+    let (_, rest) = split_string(rest).ok()?;
+    let (root, _) = split_string(rest).ok()?;
+    if root.len() != HASH_LENGTH {
+        return None;
+    }
+    Some(bytes_to_hash(root))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use sha3::{Digest, Keccak256};
+
+    use super::*;
+    use crate::trie::types::Reader;
+
+    /// NodeStore is a database of hand encoded nodes, keyed by owner and hash.
+    #[derive(Default, Clone)]
+    struct NodeStore {
+        nodes: HashMap<(Hash, Hash), Vec<u8>>,
+    }
+
+    impl NodeStore {
+        fn put(&mut self, owner: Hash, blob: Vec<u8>) -> Hash {
+            let hash: Hash = Keccak256::digest(&blob).into();
+            self.nodes.insert((owner, hash), blob);
+            hash
+        }
+    }
+
+    impl Reader for NodeStore {
+        fn node(&self, owner: Hash, _path: Option<Vec<u8>>, hash: Hash) -> Result<Vec<u8>, ()> {
+            self.nodes.get(&(owner, hash)).cloned().ok_or(())
+        }
+    }
+
+    impl Database for NodeStore {
+        fn reader(&self, _state_root: &Hash) -> Result<Box<dyn Reader>, std::io::Error> {
+            Ok(Box::new(self.clone()))
+        }
+    }
+
+    fn header(base: u8, len: usize) -> Vec<u8> {
+        if len < 56 {
+            return vec![base + len as u8];
+        }
+        let be = len.to_be_bytes();
+        let be = &be[be.iter().position(|&b| b != 0).unwrap()..];
+        [&[base + 55 + be.len() as u8], be].concat()
+    }
+
+    fn string(b: &[u8]) -> Vec<u8> {
+        if b.len() == 1 && b[0] < 0x80 {
+            return b.to_vec();
+        }
+        [header(0x80, b.len()), b.to_vec()].concat()
+    }
+
+    fn list(items: &[Vec<u8>]) -> Vec<u8> {
+        let content = items.concat();
+        [header(0xC0, content.len()), content].concat()
+    }
+
+    /// Returns a full node with the given children, which are encoded nodes
+    /// or strings. Other slots are empty.
+    fn full(children: &[(usize, Vec<u8>)]) -> Vec<u8> {
+        let mut slots = vec![string(&[]); 17];
+        for (i, child) in children {
+            slots[*i] = child.clone();
+        }
+        list(&slots)
+    }
+
+    /// Returns a leaf holding the last 63 nibbles of key, below a full node.
+    fn leaf63(key: &Hash, value: &[u8]) -> Vec<u8> {
+        let compact = [&[0x30 | (key[0] & 0x0F)], &key[1..]].concat();
+        list(&[string(&compact), string(value)])
+    }
+
+    #[test]
+    fn node_stats() {
+        let mut db = NodeStore::default();
+        // The key 0x1a holds a long value, so its leaf is hashed, while the
+        // leaf of 0x2b is embedded in the root. The empty key holds its value
+        // in the value slot of the root.
+        let hashed_leaf = list(&[string(&[0x3A]), string(&[0x11; 40])]);
+        let embedded_leaf = list(&[string(&[0x3B]), string(b"xy")]);
+        let hashed = db.put([0; 32], hashed_leaf.clone());
+        let root_blob = full(&[
+            (1, string(&hashed)),
+            (2, embedded_leaf.clone()),
+            (16, string(b"v")),
+        ]);
+        let root = db.put([0; 32], root_blob.clone());
+
+        let stats = inspect(root, &db).unwrap();
+        assert_eq!(stats.full_nodes, 1);
+        assert_eq!(stats.short_nodes, 2);
+        assert_eq!(stats.value_nodes, 3);
+        assert_eq!(stats.hash_nodes, 2);
+        assert_eq!(stats.leaves, 3);
+        assert_eq!(stats.leaf_depths, [1, 2]);
+        assert_eq!(stats.embedded_children, 1);
+        assert_eq!(stats.hashed_children, 1);
+        assert_eq!(stats.full_size, root_blob.len());
+        assert_eq!(stats.short_size, hashed_leaf.len() + embedded_leaf.len());
+        assert_eq!(stats.value_size, 41 + 3 + 1);
+        assert_eq!(stats.avg_short_size(), 24.0);
+        assert_eq!(
+            stats.total_size(),
+            root_blob.len() + hashed_leaf.len() + embedded_leaf.len() + 45
+        );
+    }
+
+    #[test]
+    fn missing_nodes() {
+        let mut db = NodeStore::default();
+        let root = db.put(
+            [0; 32],
+            full(&[(1, string(&[0xAA; 32])), (2, string(b"x"))]),
+        );
+        assert!(inspect(root, &db).is_err());
+        assert!(inspect([0xAA; 32], &db).is_err());
+    }
+
+    #[test]
+    fn empty_trie() {
+        let stats = inspect(EMPTY_ROOT_HASH, &NodeStore::default()).unwrap();
+        assert_eq!(stats.leaves, 0);
+        assert_eq!(stats.total_size(), 0);
+    }
+
+    /// Stores a storage trie of owner with n slots and returns its root.
+    fn storage_trie(db: &mut NodeStore, owner: Hash, n: u8) -> Hash {
+        let leaves: Vec<(usize, Vec<u8>)> = (0..n)
+            .map(|i| {
+                let leaf = leaf63(&[i << 4; 32], &[1]);
+                (i as usize, string(&db.put(owner, leaf)))
+            })
+            .collect();
+        db.put(owner, full(&leaves))
+    }
+
+    fn account(storage_root: Hash) -> Vec<u8> {
+        list(&[
+            string(&[]),
+            string(&[]),
+            string(&storage_root),
+            string(&[0xCC; 32]),
+        ])
+    }
+
+    #[test]
+    fn largest_storage_tries() {
+        let mut db = NodeStore::default();
+        let slots = [(0x11, 2), (0x22, 3), (0x33, 0), (0x44, 3)];
+        let mut accounts = Vec::new();
+        for (b, n) in slots {
+            let owner = [b; 32];
+            let storage_root = if n == 0 {
+                EMPTY_ROOT_HASH
+            } else {
+                storage_trie(&mut db, owner, n)
+            };
+            let leaf = db.put([0; 32], leaf63(&owner, &account(storage_root)));
+            accounts.push(((b >> 4) as usize, string(&leaf)));
+        }
+        let root = db.put([0; 32], full(&accounts));
+
+        let stats = inspect_state(root, &db, 2).unwrap();
+        assert_eq!(stats.accounts.leaves, 4);
+        assert_eq!(stats.storage_tries, 3);
+        // Largest first, ties ordered by owner.
+        let largest: Vec<(Hash, usize)> = stats
+            .largest_storage
+            .iter()
+            .map(|(owner, stats)| (*owner, stats.leaves))
+            .collect();
+        assert_eq!(largest, [([0x22; 32], 3), ([0x44; 32], 3)]);
+
+        let stats = inspect_state(root, &db, 10).unwrap();
+        assert_eq!(stats.largest_storage.len(), 3);
+        assert_eq!(stats.largest_storage[2].0, [0x11; 32]);
+        assert_eq!(stats.largest_storage[2].1.leaves, 2);
+        assert!(inspect_state(root, &db, 0)
+            .unwrap()
+            .largest_storage
+            .is_empty());
+    }
+}
@@ -1,9 +1,20 @@
-mod node;
-mod trie;
-mod trie_id;
-mod trie_reader;
-mod types;
-mod encoding;
-mod hash;
+// Trie operations report missing and malformed nodes as `Err(())`. The
+// convention predates the rest of the crate and stays within the trie layer,
+// code outside it returns proper error types.
+#![allow(clippy::result_unit_err)]
+
+mod committer;
+pub mod encoding;
+pub mod hash;
+pub mod inspect;
+pub mod node;
+mod printer;
+mod state_trie;
+pub mod proof;
 mod node_encoder;
+#[allow(clippy::module_inception)]
+mod trie;
+pub mod trie_id;
+pub mod trie_reader;
+pub mod types;
 pub use trie::*;
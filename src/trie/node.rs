@@ -1,13 +1,8 @@
-use crate::rlp::{
-    self,
-    decode::{count_values, split_list, split_string},
-    encoder_buffer::EMPTY_STRING,
-    rlp_encoder::RlpEncoder,
-};
+use crate::rlp::{decode::Kind, Decodable, RawRlp, RlpList, RlpListIter};
 
-use super::encoding::{compact_to_hex, has_term, hex_to_compact};
+use super::{encoding::{compact_to_hex, has_term}, types::HASH_LENGTH};
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub enum Node {
     FullNode(FullNode),
     ShortNode(ShortNode),
@@ -16,7 +11,7 @@ pub enum Node {
     Empty,
 }
 
-static INDICES: &[&str] = &[
+pub static INDICES: &[&str] = &[
     "0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "a", "b", "c", "d", "e", "f", "[17]",
 ];
 
@@ -25,7 +20,7 @@ impl Node {
     pub fn encode_rlp() {}
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct ShortNode {
     pub key: Vec<u8>,
     pub val: Box<Node>,
@@ -36,18 +31,22 @@ pub type HashNode = Vec<u8>;
 
 pub type ValueNode = Vec<u8>;
 
-#[derive(Clone, Default)]
+#[derive(Clone, Debug)]
 pub struct FullNode {
     pub children: Vec<Node>,
     pub flags: NodeFlag,
 }
 
-// rawNode is a simple binary blob used to differentiate between collapsed trie
-// nodes and already encoded RLP binary blobs (while at the same time store them
-// in the same cache fields).
-type RawNode = Vec<u8>;
+impl Default for FullNode {
+    fn default() -> Self {
+        Self {
+            children: vec![Node::Empty; 17],
+            flags: NodeFlag::default(),
+        }
+    }
+}
 
-#[derive(Clone, Default)]
+#[derive(Clone, Debug, Default)]
 // nodeFlag contains caching-related metadata about a node.
 pub struct NodeFlag {
     pub hash: Option<HashNode>, // cached hash of the node (may be nil)
@@ -66,86 +65,106 @@ impl ShortNode {
     }
 }
 
-pub fn must_decode_node(hash: Vec<u8>, buff: Vec<u8>) -> Node {
-    let n = decode_node(hash, buff).expect("Not Expected to Fail");
-    n
+pub fn must_decode_node(hash: Option<HashNode>, buff: &[u8]) -> Node {
+    decode_node(hash, buff).expect("Not Expected to Fail")
 }
 
-pub fn decode_node(hash: Vec<u8>, buff: Vec<u8>) -> Result<Node, ()> {
-    if buff.len() == 0 {}
-
-    let (elems, _) = split_list(&buff[..]).unwrap();
-
-    let count = count_values(&elems).unwrap();
+/// Parses the RLP encoding of a trie node. The hash is the one the node was
+/// resolved by, and is `None` for nodes embedded in their parent.
+pub fn decode_node(hash: Option<HashNode>, mut buff: &[u8]) -> Result<Node, ()> {
+    if buff.is_empty() {
+        return Err(());
+    }
 
-    match count {
-        2 => {
-            let node = decode_short(hash, elems)?;
+    let elems: RlpList = Decodable::decode(&mut buff).map_err(|_| ())?;
 
-            return Ok(Node::ShortNode(node));
-        }
-        17 => {
-            let node = decode_full(hash, elems)?;
-            return Ok(Node::FullNode(node));
-        }
+    match elems.len().map_err(|_| ())? {
+        2 => Ok(Node::ShortNode(decode_short(hash, elems)?)),
+        17 => Ok(Node::FullNode(decode_full(hash, elems)?)),
         _ => Err(()),
     }
 }
 
-pub fn decode_short(hash: Vec<u8>, elems: &[u8]) -> Result<ShortNode, ()> {
-    let (content, rest) = split_string(elems)?;
+pub fn decode_short(hash: Option<HashNode>, elems: RlpList) -> Result<ShortNode, ()> {
+    let mut items = elems.iter();
+    let kbuf = next_item(&mut items)?;
+    if kbuf.kind() == Kind::List {
+        return Err(());
+    }
 
     let flag = NodeFlag {
-        hash: Some(hash),
+        hash,
         ..Default::default()
     };
-    let key = compact_to_hex(content);
+    let key = compact_to_hex(kbuf.content());
+    let rest = next_item(&mut items)?;
 
     if has_term(&key) {
         //value node
-        let (val, _) = split_string(rest)?;
+        if rest.kind() == Kind::List {
+            return Err(());
+        }
 
         return Ok(ShortNode {
             key,
-            val: Box::new(Node::ValueNode(val.to_vec())),
+            val: Box::new(Node::ValueNode(rest.content().to_vec())),
             flags: flag,
         });
     }
 
-    let (node, _) = decode_ref(rest)?;
-
-    return Ok(ShortNode {
+    Ok(ShortNode {
         key,
-        val: Box::new(node),
+        val: Box::new(decode_ref(rest)?),
         flags: flag,
-    });
+    })
 }
 
-pub fn decode_full(hash: Vec<u8>, mut elems: &[u8]) -> Result<FullNode, ()> {
+pub fn decode_full(hash: Option<HashNode>, elems: RlpList) -> Result<FullNode, ()> {
     let mut node = FullNode {
         flags: NodeFlag {
-            hash: Some(hash),
+            hash,
             ..Default::default()
         },
         ..Default::default()
     };
 
+    let mut items = elems.iter();
     for i in 0..16 {
-        let (child_node, rest) = decode_ref(elems)?;
-
-        node.children[i] = child_node;
-        elems = rest;
+        node.children[i] = decode_ref(next_item(&mut items)?)?;
     }
 
-    let (val, _) = split_string(elems)?;
+    let val = next_item(&mut items)?;
+    if val.kind() == Kind::List {
+        return Err(());
+    }
 
-    if val.len() > 0 {
-        node.children[16] = Node::ValueNode(val.to_vec());
+    if !val.content().is_empty() {
+        node.children[16] = Node::ValueNode(val.content().to_vec());
     }
 
-    return Ok(node);
+    Ok(node)
 }
 
-fn decode_ref(buff: &[u8]) -> Result<(Node, &[u8]), ()> {
-    unimplemented!()
+fn next_item<'a>(items: &mut RlpListIter<'a>) -> Result<RawRlp<'a>, ()> {
+    items.next().ok_or(())?.map_err(|_| ())
+}
+
+/// Decodes a child reference of a short or full node: either a node embedded
+/// in its parent, an empty slot or the hash of a node stored separately.
+fn decode_ref(buff: RawRlp) -> Result<Node, ()> {
+    let val = buff.content();
+
+    match buff.kind() {
+        Kind::List => {
+            // 'embedded' node reference. The encoding must be smaller
+            // than a hash in order to be valid.
+            if buff.as_bytes().len() > HASH_LENGTH {
+                return Err(());
+            }
+            decode_node(None, buff.as_bytes())
+        }
+        Kind::String if val.is_empty() => Ok(Node::Empty),
+        Kind::String if val.len() == HASH_LENGTH => Ok(Node::HashNode(val.to_vec())),
+        _ => Err(()),
+    }
 }
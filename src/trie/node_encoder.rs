@@ -1,49 +1,66 @@
-use crate::rlp::{encoder_buffer::EMPTY_STRING, rlp_encoder::RlpEncoder};
+use crate::rlp::{bytes_len, list_len, rlp_encoder::RlpEncoder, Encodable};
 
 use super::node::{FullNode, Node, ShortNode};
 
 
-impl FullNode {
-    pub fn encode(&self, rlp_enc: &mut RlpEncoder) {
+impl Encodable for FullNode {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
         let offset = rlp_enc.list();
         //Encode all the children in the Full Node
-        for child in self.children.clone() {
+        for child in &self.children {
             child.encode(rlp_enc);
         }
 
         rlp_enc.list_end(offset);
     }
+
+    fn encoded_len(&self) -> usize {
+        list_len(self.children.iter().map(Encodable::encoded_len).sum())
+    }
 }
 
-impl ShortNode {
-    pub fn encode(&self, rlp_enc: &mut RlpEncoder) {
+impl Encodable for ShortNode {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
         let offset = rlp_enc.list();
-        rlp_enc.write(self.key.clone());
+        rlp_enc.write_bytes(&self.key);
 
         self.val.as_ref().encode(rlp_enc);
 
         rlp_enc.list_end(offset);
     }
+
+    fn encoded_len(&self) -> usize {
+        list_len(bytes_len(&self.key) + self.val.encoded_len())
+    }
 }
 
-impl Node {
-    pub fn encode(&self, rlp_enc: &mut RlpEncoder) {
+impl Encodable for Node {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
         match self {
             Node::FullNode(n) => {
                 n.encode(rlp_enc);
             }
             Node::ShortNode(short_node) => {
-                short_node.val.encode(rlp_enc);
+                short_node.encode(rlp_enc);
             }
             Node::HashNode(n) => {
-                rlp_enc.write_bytes(n.to_vec());
+                rlp_enc.write_bytes(n);
             }
             Node::ValueNode(vn) => {
-                rlp_enc.write_bytes(vn.to_vec());
+                rlp_enc.write_bytes(vn);
             }
             Node::Empty => {
-                rlp_enc.write(EMPTY_STRING.to_vec());
+                rlp_enc.write_bytes(&[]);
             }
         }
     }
+
+    fn encoded_len(&self) -> usize {
+        match self {
+            Node::FullNode(n) => n.encoded_len(),
+            Node::ShortNode(n) => n.encoded_len(),
+            Node::HashNode(b) | Node::ValueNode(b) => bytes_len(b),
+            Node::Empty => bytes_len(&[]),
+        }
+    }
 }
@@ -0,0 +1,312 @@
+use std::fmt::{self, Write};
+
+use crate::utils::to_hex;
+
+use super::{
+    encoding::has_term,
+    node::{Node, NodeFlag, INDICES},
+    Trie,
+};
+
+/// Formats a hex key as a string of nibbles, marking keys ending in the
+/// terminator as leaves.
+fn key_string(key: &[u8]) -> String {
+    let (nibbles, leaf) = if has_term(key) {
+        (&key[..key.len() - 1], " (leaf)")
+    } else {
+        (key, "")
+    };
+    let nibbles: String = nibbles.iter().map(|n| INDICES[*n as usize]).collect();
+    format!("[{}]{}", nibbles, leaf)
+}
+
+fn flags_string(flags: &NodeFlag) -> String {
+    let mut s = String::new();
+    if let Some(hash) = &flags.hash {
+        s.push_str(&format!(" <0x{}>", to_hex(hash)));
+    }
+    if flags.dirty {
+        s.push_str(" dirty");
+    }
+    s
+}
+
+impl Node {
+    /// Writes the node and its children, one node per line, with children
+    /// indented two spaces deeper than their parent.
+    fn fstring(&self, f: &mut impl Write, ind: &str) -> fmt::Result {
+        match self {
+            Node::FullNode(n) => {
+                writeln!(f, "full{}", flags_string(&n.flags))?;
+                for (i, child) in n.children.iter().enumerate() {
+                    if let Node::Empty = child {
+                        continue;
+                    }
+                    write!(f, "{}  {}: ", ind, INDICES[i])?;
+                    child.fstring(f, &format!("{}  ", ind))?;
+                }
+                Ok(())
+            }
+            Node::ShortNode(n) => {
+                writeln!(f, "short {}{}", key_string(&n.key), flags_string(&n.flags))?;
+                write!(f, "{}  ", ind)?;
+                n.val.fstring(f, &format!("{}  ", ind))
+            }
+            Node::HashNode(hash) => writeln!(f, "hash <0x{}>", to_hex(hash)),
+            Node::ValueNode(value) => writeln!(f, "value 0x{}", to_hex(value)),
+            Node::Empty => writeln!(f, "empty"),
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fstring(f, "")
+    }
+}
+
+impl fmt::Display for Trie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.root {
+            Some(root) => root.fstring(f, ""),
+            None => writeln!(f, "empty"),
+        }
+    }
+}
+
+impl fmt::Debug for Trie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Trie")
+            .field("root", &self.root)
+            .field("owner", &format_args!("0x{}", to_hex(&self.owner)))
+            .field("committed", &self.committed)
+            .field("unhashed", &self.unhashed)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Shortens long byte strings for graph labels.
+fn short_hex(b: &[u8]) -> String {
+    if b.len() <= 8 {
+        return format!("0x{}", to_hex(b));
+    }
+    format!("0x{}…", to_hex(&b[..8]))
+}
+
+struct DotWriter {
+    out: String,
+    next_id: usize,
+}
+
+impl DotWriter {
+    /// Writes the node and its children, returning the graph id of the node.
+    fn node(&mut self, node: &Node) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        match node {
+            Node::FullNode(n) => {
+                self.vertex(id, "full", &n.flags, "box");
+                for (i, child) in n.children.iter().enumerate() {
+                    if let Node::Empty = child {
+                        continue;
+                    }
+                    let child_id = self.node(child);
+                    self.edge(id, child_id, INDICES[i]);
+                }
+            }
+            Node::ShortNode(n) => {
+                let label = format!("short\\n{}", key_string(&n.key));
+                self.vertex(id, &label, &n.flags, "box");
+                let child_id = self.node(&n.val);
+                self.edge(id, child_id, "");
+            }
+            Node::HashNode(hash) => {
+                // Unresolved reference to a node which is not loaded.
+                let _ = writeln!(
+                    self.out,
+                    "  n{} [label=\"hash\\n{}\", shape=ellipse, style=dashed];",
+                    id,
+                    short_hex(hash)
+                );
+            }
+            Node::ValueNode(value) => {
+                let _ = writeln!(
+                    self.out,
+                    "  n{} [label=\"{}\", shape=note];",
+                    id,
+                    short_hex(value)
+                );
+            }
+            Node::Empty => {
+                let _ = writeln!(self.out, "  n{} [label=\"empty\", shape=point];", id);
+            }
+        }
+
+        id
+    }
+
+    fn vertex(&mut self, id: usize, label: &str, flags: &NodeFlag, shape: &str) {
+        let mut label = label.to_string();
+        if let Some(hash) = &flags.hash {
+            label.push_str(&format!("\\n{}", short_hex(hash)));
+        }
+        let style = if flags.dirty {
+            ", style=filled, fillcolor=lightpink"
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            self.out,
+            "  n{} [label=\"{}\", shape={}{}];",
+            id, label, shape, style
+        );
+    }
+
+    fn edge(&mut self, from: usize, to: usize, label: &str) {
+        let _ = writeln!(self.out, "  n{} -> n{} [label=\"{}\"];", from, to, label);
+    }
+}
+
+impl Trie {
+    /// Exports the loaded part of the trie as a Graphviz graph. Dirty nodes
+    /// are filled, and hash nodes which have not been resolved from the
+    /// database are drawn as dashed ellipses.
+    pub fn to_dot(&self) -> String {
+        let mut w = DotWriter {
+            out: String::from("digraph trie {\n  node [fontname=\"monospace\"];\n"),
+            next_id: 0,
+        };
+        if let Some(root) = &self.root {
+            w.node(root);
+        }
+        w.out.push_str("}\n");
+
+        w.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trie::{
+        node::{FullNode, ShortNode},
+        trie::new_empty,
+    };
+
+    fn leaf(key: &[u8], value: &[u8]) -> Node {
+        Node::ShortNode(ShortNode {
+            key: key.to_vec(),
+            val: Box::new(Node::ValueNode(value.to_vec())),
+            flags: NodeFlag::default(),
+        })
+    }
+
+    /// Returns a dirty full node with a cached hash, an unresolved child at
+    /// slot 1, a leaf at slot 2 and a value in the value slot.
+    fn branch() -> Node {
+        let mut n = FullNode {
+            flags: NodeFlag {
+                hash: Some(vec![0xcd; 32]),
+                dirty: true,
+            },
+            ..Default::default()
+        };
+        n.children[1] = Node::HashNode(vec![0xab; 32]);
+        n.children[2] = leaf(&[3, 16], &[0x01, 0x02]);
+        n.children[16] = Node::ValueNode(vec![0x09]);
+        Node::FullNode(n)
+    }
+
+    #[test]
+    fn display_short() {
+        assert_eq!(
+            leaf(&[1, 0xa, 16], b"hi").to_string(),
+            "short [1a] (leaf)\n  value 0x6869\n"
+        );
+
+        let extension = Node::ShortNode(ShortNode {
+            key: vec![1, 2],
+            val: Box::new(Node::HashNode(vec![0xab; 32])),
+            flags: NodeFlag {
+                hash: None,
+                dirty: true,
+            },
+        });
+        assert_eq!(
+            extension.to_string(),
+            format!("short [12] dirty\n  hash <0x{}>\n", "ab".repeat(32))
+        );
+    }
+
+    #[test]
+    fn display_full() {
+        assert_eq!(
+            branch().to_string(),
+            format!(
+                "full <0x{}> dirty\n  1: hash <0x{}>\n  2: short [3] (leaf)\n    value 0x0102\n  [17]: value 0x09\n",
+                "cd".repeat(32),
+                "ab".repeat(32)
+            )
+        );
+    }
+
+    #[test]
+    fn display_trie() {
+        let mut trie = new_empty();
+        assert_eq!(trie.to_string(), "empty\n");
+
+        trie.root = Some(leaf(&[1, 16], b"v"));
+        assert_eq!(trie.to_string(), "short [1] (leaf)\n  value 0x76\n");
+
+        // Hashing caches the hash in the node flags.
+        let hash = trie.hash();
+        assert_eq!(
+            trie.to_string(),
+            format!("short [1] (leaf) <0x{}>\n  value 0x76\n", to_hex(&hash))
+        );
+    }
+
+    #[test]
+    fn debug() {
+        assert_eq!(
+            format!("{:?}", leaf(&[1, 16], &[7])),
+            "ShortNode(ShortNode { key: [1, 16], val: ValueNode([7]), flags: NodeFlag { hash: None, dirty: false } })"
+        );
+
+        let mut trie = new_empty();
+        trie.root = Some(Node::ValueNode(vec![7]));
+        assert_eq!(
+            format!("{:?}", trie),
+            format!(
+                "Trie {{ root: Some(ValueNode([7])), owner: 0x{}, committed: None, unhashed: None, .. }}",
+                "00".repeat(32)
+            )
+        );
+    }
+
+    #[test]
+    fn dot() {
+        let mut trie = new_empty();
+        assert_eq!(
+            trie.to_dot(),
+            "digraph trie {\n  node [fontname=\"monospace\"];\n}\n"
+        );
+
+        trie.root = Some(branch());
+        let expected = r#"digraph trie {
+  node [fontname="monospace"];
+  n0 [label="full\n0xcdcdcdcdcdcdcdcd…", shape=box, style=filled, fillcolor=lightpink];
+  n1 [label="hash\n0xabababababababab…", shape=ellipse, style=dashed];
+  n0 -> n1 [label="1"];
+  n2 [label="short\n[3] (leaf)", shape=box];
+  n3 [label="0x0102", shape=note];
+  n2 -> n3 [label=""];
+  n0 -> n2 [label="2"];
+  n4 [label="0x09", shape=note];
+  n0 -> n4 [label="[17]"];
+}
+"#;
+        assert_eq!(trie.to_dot(), expected);
+    }
+}
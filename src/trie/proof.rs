@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use crate::utils::{bytes_to_hash, keccak256};
+
+use super::{
+    encoding::keybytes_to_hex,
+    hash::Hasher,
+    node::{decode_node, Node},
+    types::Hash,
+    Trie,
+};
+
+impl Trie {
+    /// Constructs a merkle proof for key. The result contains all encoded nodes
+    /// on the path to the value at key, starting with the root node. The value
+    /// itself is also included in the last node and can be retrieved by
+    /// verifying the proof.
+    ///
+    /// If the trie does not contain a value for key, the returned proof contains
+    /// all nodes of the longest existing prefix of the key (at least the root
+    /// node), ending with the node that proves the absence of the key.
+    pub fn prove(&self, key: &[u8]) -> Result<Vec<Vec<u8>>, ()> {
+        // The trie is unusable after a commit.
+        if self.committed == Some(true) {
+            return Err(());
+        }
+
+        // Collect all nodes on the path to key.
+        let key = keybytes_to_hex(key);
+        let mut key = key.as_slice();
+        let mut prefix = Vec::new();
+        let mut nodes = Vec::new();
+        let mut tn = self.root.clone().unwrap_or(Node::Empty);
+
+        while !key.is_empty() {
+            match tn {
+                Node::ShortNode(n) => {
+                    if !key.starts_with(&n.key) {
+                        // The trie doesn't contain the key.
+                        tn = Node::Empty;
+                    } else {
+                        tn = n.val.as_ref().clone();
+                        prefix.extend_from_slice(&n.key);
+                        key = &key[n.key.len()..];
+                    }
+                    nodes.push(Node::ShortNode(n));
+                }
+                Node::FullNode(n) => {
+                    tn = n.children[key[0] as usize].clone();
+                    prefix.push(key[0]);
+                    key = &key[1..];
+                    nodes.push(Node::FullNode(n));
+                }
+                Node::HashNode(hash) => {
+                    // Retrieve the specified node from the underlying node reader.
+                    let blob = self
+                        .reader
+                        .node(Some(prefix.clone()), bytes_to_hash(&hash))?;
+                    tn = decode_node(Some(hash), &blob)?;
+                }
+                Node::Empty | Node::ValueNode(_) => break,
+            }
+        }
+
+        let mut hasher = Hasher::new();
+        let mut proof = Vec::with_capacity(nodes.len());
+        for (i, n) in nodes.iter().enumerate() {
+            let (collapsed, hashed) = hasher.proof_hash(n);
+            if matches!(hashed, Node::HashNode(_)) || i == 0 {
+                // If the node's database encoding is a hash (or is the
+                // root node), it becomes a proof element.
+                proof.push(hasher.node_to_bytes(&collapsed));
+            }
+        }
+
+        Ok(proof)
+    }
+}
+
+/// Checks merkle proofs. The given proof must contain the value for key in a
+/// trie with the given root hash. Returns `Ok(None)` if the proof shows that
+/// the key is absent, and an error if the proof is missing nodes on the path
+/// to key or contains invalid trie nodes.
+pub fn verify_proof(root: Hash, key: &[u8], proof: &[Vec<u8>]) -> Result<Option<Vec<u8>>, ()> {
+    let proof: HashMap<Hash, &[u8]> = proof
+        .iter()
+        .map(|node| (keccak256(node), node.as_slice()))
+        .collect();
+
+    let key = keybytes_to_hex(key);
+    let mut key = key.as_slice();
+    let mut want_hash = root;
+    loop {
+        // proof node missing
+        let buf = proof.get(&want_hash).ok_or(())?;
+        let node = decode_node(Some(want_hash.to_vec()), buf)?;
+
+        let (keyrest, child) = get(node, key);
+        match child {
+            // The trie doesn't contain the key.
+            Node::Empty => return Ok(None),
+            Node::HashNode(hash) => {
+                key = keyrest;
+                want_hash = bytes_to_hash(&hash);
+            }
+            Node::ValueNode(value) => return Ok(Some(value)),
+            Node::FullNode(_) | Node::ShortNode(_) => unreachable!(),
+        }
+    }
+}
+
+/// Walks the node tn along key, skipping over embedded nodes, and returns the
+/// first hash node or value reached along with the remaining key.
+fn get(mut tn: Node, mut key: &[u8]) -> (&[u8], Node) {
+    loop {
+        match tn {
+            Node::ShortNode(n) => {
+                if !key.starts_with(&n.key) {
+                    return (key, Node::Empty);
+                }
+                key = &key[n.key.len()..];
+                tn = *n.val;
+            }
+            Node::FullNode(mut n) => {
+                if key.is_empty() {
+                    return (key, Node::Empty);
+                }
+                tn = std::mem::replace(&mut n.children[key[0] as usize], Node::Empty);
+                key = &key[1..];
+            }
+            Node::HashNode(_) | Node::Empty | Node::ValueNode(_) => return (key, tn),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trie::{new_empty, types::EMPTY_ROOT_HASH};
+
+    fn test_trie() -> Trie {
+        let mut trie = new_empty();
+        for (key, value) in [
+            ("doe", "reindeer"),
+            ("dog", "puppy"),
+            ("dogglesworth", "cat"),
+        ] {
+            trie.update(key.as_bytes(), value.as_bytes()).unwrap();
+        }
+        trie
+    }
+
+    #[test]
+    fn prove_and_verify() {
+        let mut trie = test_trie();
+        let root = trie.hash();
+        for (key, value) in [
+            ("doe", "reindeer"),
+            ("dog", "puppy"),
+            ("dogglesworth", "cat"),
+        ] {
+            let proof = trie.prove(key.as_bytes()).unwrap();
+            assert_eq!(keccak256(&proof[0]), root);
+            assert_eq!(
+                verify_proof(root, key.as_bytes(), &proof),
+                Ok(Some(value.as_bytes().to_vec()))
+            );
+        }
+    }
+
+    #[test]
+    fn prove_absence() {
+        let mut trie = test_trie();
+        let root = trie.hash();
+        for key in ["do", "dogs", "cat", ""] {
+            let proof = trie.prove(key.as_bytes()).unwrap();
+            assert!(!proof.is_empty());
+            assert_eq!(verify_proof(root, key.as_bytes(), &proof), Ok(None));
+        }
+
+        let empty = new_empty();
+        assert_eq!(empty.prove(b"dog"), Ok(Vec::new()));
+        assert_eq!(verify_proof(EMPTY_ROOT_HASH, b"dog", &[]), Err(()));
+    }
+
+    #[test]
+    fn bad_proof() {
+        let mut trie = test_trie();
+        let root = trie.hash();
+        let proof = trie.prove(b"dogglesworth").unwrap();
+
+        // A missing node breaks the path to the value.
+        let missing = &proof[..proof.len() - 1];
+        assert_eq!(verify_proof(root, b"dogglesworth", missing), Err(()));
+
+        // A modified node no longer matches the hash referencing it.
+        let mut modified = proof.clone();
+        let last = modified.last_mut().unwrap();
+        *last.last_mut().unwrap() ^= 1;
+        assert_eq!(verify_proof(root, b"dogglesworth", &modified), Err(()));
+
+        // The proof is not valid for another root.
+        assert_eq!(verify_proof([1; 32], b"dogglesworth", &proof), Err(()));
+    }
+}
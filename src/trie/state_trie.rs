@@ -0,0 +1,217 @@
+use crate::{
+    rlp,
+    types::account::StateAccount,
+    utils::{keccak256, trim_left_zeroes},
+};
+
+use super::{
+    types::{Hash, ADDRESS_LENGTH},
+    Trie,
+};
+
+/// Account access on top of the trie. Accounts are keyed by the Keccak-256
+/// hash of their address and stored in their consensus RLP encoding.
+impl Trie {
+    /// Returns the account stored at address, or `None` if the account does
+    /// not exist.
+    pub fn get_account(&mut self, address: &[u8]) -> Result<Option<StateAccount>, ()> {
+        let key = hash_address(address)?;
+        match self.get(&key)? {
+            Some(blob) => Ok(Some(rlp::decode(&blob).map_err(|_| ())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Writes the account to the trie at address.
+    pub fn update_account(&mut self, address: &[u8], account: &StateAccount) -> Result<(), ()> {
+        let key = hash_address(address)?;
+        self.update(&key, &rlp::encode(account))
+    }
+
+    /// Removes the account at address from the trie.
+    pub fn delete_account(&mut self, address: &[u8]) -> Result<(), ()> {
+        let key = hash_address(address)?;
+        self.delete(&key)
+    }
+}
+
+fn hash_address(address: &[u8]) -> Result<Hash, ()> {
+    if address.len() != ADDRESS_LENGTH {
+        return Err(());
+    }
+
+    Ok(keccak256(address))
+}
+
+/// Storage access on top of a storage trie. Slots are keyed by the
+/// Keccak-256 hash of the slot key, and values are stored as RLP strings with
+/// leading zeroes trimmed.
+impl Trie {
+    /// Returns the value of the storage slot, or `None` if the slot is empty.
+    pub fn get_storage(&mut self, slot: &[u8]) -> Result<Option<Vec<u8>>, ()> {
+        match self.get(&keccak256(slot))? {
+            Some(enc) => Ok(Some(rlp::decode(&enc).map_err(|_| ())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Writes value to the storage slot. A value consisting only of zeroes
+    /// deletes the slot.
+    pub fn update_storage(&mut self, slot: &[u8], value: &[u8]) -> Result<(), ()> {
+        let value = trim_left_zeroes(value);
+        if value.is_empty() {
+            return self.delete_storage(slot);
+        }
+
+        self.update(&keccak256(slot), &rlp::encode(value))
+    }
+
+    /// Removes the storage slot from the trie.
+    pub fn delete_storage(&mut self, slot: &[u8]) -> Result<(), ()> {
+        self.delete(&keccak256(slot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        database::MemoryDatabase,
+        trie::{
+            trie::{new, new_empty},
+            trie_id::storage_trie_id,
+            types::EMPTY_ROOT_HASH,
+        },
+        types::u256::U256,
+    };
+
+    fn account(nonce: u64) -> StateAccount {
+        StateAccount {
+            nonce,
+            balance: U256::from(1000 * nonce),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn accounts() {
+        let mut trie = new_empty();
+        trie.update_account(&[1; 20], &account(1)).unwrap();
+        trie.update_account(&[2; 20], &account(2)).unwrap();
+
+        assert_eq!(trie.get_account(&[1; 20]).unwrap(), Some(account(1)));
+        assert_eq!(trie.get_account(&[2; 20]).unwrap(), Some(account(2)));
+        assert_eq!(trie.get_account(&[3; 20]).unwrap(), None);
+
+        // Accounts are keyed by the hash of their address.
+        assert_eq!(
+            trie.get(&keccak256(&[1; 20])).unwrap(),
+            Some(rlp::encode(&account(1)))
+        );
+    }
+
+    #[test]
+    fn delete_account() {
+        let mut trie = new_empty();
+        trie.update_account(&[1; 20], &account(1)).unwrap();
+        trie.update_account(&[2; 20], &account(2)).unwrap();
+
+        trie.delete_account(&[1; 20]).unwrap();
+        assert_eq!(trie.get_account(&[1; 20]).unwrap(), None);
+        assert_eq!(trie.get_account(&[2; 20]).unwrap(), Some(account(2)));
+
+        trie.delete_account(&[2; 20]).unwrap();
+        assert_eq!(trie.get_account(&[2; 20]).unwrap(), None);
+        assert_eq!(trie.hash(), EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn address_length() {
+        let mut trie = new_empty();
+        for address in [&[1; 19][..], &[1; 21], &[1; 32], &[]] {
+            assert_eq!(trie.get_account(address), Err(()));
+            assert_eq!(trie.update_account(address, &account(1)), Err(()));
+            assert_eq!(trie.delete_account(address), Err(()));
+        }
+        assert_eq!(trie.hash(), EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn invalid_account() {
+        let mut trie = new_empty();
+        trie.update(&keccak256(&[1; 20]), &[0xc1, 0x80]).unwrap();
+        assert_eq!(trie.get_account(&[1; 20]), Err(()));
+    }
+
+    #[test]
+    fn storage_values() {
+        let mut trie = new_empty();
+        trie.update_storage(&[1; 32], &[0, 0, 0x12, 0x34]).unwrap();
+        trie.update_storage(&[2; 32], &[0x05]).unwrap();
+
+        // Values are left-trimmed before they are RLP encoded.
+        assert_eq!(
+            trie.get(&keccak256(&[1; 32])).unwrap(),
+            Some(vec![0x82, 0x12, 0x34])
+        );
+        assert_eq!(trie.get(&keccak256(&[2; 32])).unwrap(), Some(vec![0x05]));
+        assert_eq!(trie.get_storage(&[1; 32]).unwrap(), Some(vec![0x12, 0x34]));
+        assert_eq!(trie.get_storage(&[2; 32]).unwrap(), Some(vec![0x05]));
+        assert_eq!(trie.get_storage(&[3; 32]).unwrap(), None);
+    }
+
+    #[test]
+    fn zero_storage_value() {
+        let mut trie = new_empty();
+        trie.update_storage(&[1; 32], &[0x01]).unwrap();
+        trie.update_storage(&[2; 32], &[0x02]).unwrap();
+
+        trie.update_storage(&[1; 32], &[0; 32]).unwrap();
+        assert_eq!(trie.get_storage(&[1; 32]).unwrap(), None);
+        trie.update_storage(&[2; 32], &[]).unwrap();
+        assert_eq!(trie.get_storage(&[2; 32]).unwrap(), None);
+        assert_eq!(trie.hash(), EMPTY_ROOT_HASH);
+
+        // Clearing a missing slot leaves the trie empty.
+        trie.update_storage(&[3; 32], &[0]).unwrap();
+        assert_eq!(trie.hash(), EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn storage_tries_by_owner() {
+        let db = MemoryDatabase::new();
+        let state_root = [0xee; 32];
+        let owners = [[0xaa; 32], [0xbb; 32]];
+
+        // Both tries hold the same slots, so their nodes share paths and
+        // only the owner keeps them apart in the database.
+        let mut roots = Vec::new();
+        for (i, owner) in owners.iter().enumerate() {
+            let mut trie = new(storage_trie_id(state_root, *owner, EMPTY_ROOT_HASH), &db).unwrap();
+            for slot in 0u8..20 {
+                trie.update_storage(&[slot; 32], &[i as u8 + 1; 32])
+                    .unwrap();
+            }
+            let (root, nodes) = trie.commit();
+            let nodes = nodes.unwrap();
+            assert_eq!(nodes.owner, *owner);
+            db.update(&nodes);
+            roots.push(root);
+        }
+        assert_ne!(roots[0], roots[1]);
+
+        for (i, owner) in owners.iter().enumerate() {
+            let mut trie = new(storage_trie_id(state_root, *owner, roots[i]), &db).unwrap();
+            for slot in 0u8..20 {
+                assert_eq!(
+                    trie.get_storage(&[slot; 32]).unwrap(),
+                    Some(vec![i as u8 + 1; 32])
+                );
+            }
+        }
+
+        // The root of one trie is not found under the other owner.
+        assert!(new(storage_trie_id(state_root, owners[1], roots[0]), &db).is_err());
+        assert!(new(storage_trie_id(state_root, [0xcc; 32], roots[0]), &db).is_err());
+    }
+}
@@ -1,14 +1,9 @@
-use std::collections::HashMap;
-
 use super::{
-    encoding::prefix_len,
+    encoding::{keybytes_to_hex, prefix_len},
     hash::Hasher,
-    node::{must_decode_node, FullNode, HashNode, Node, NodeFlag, ShortNode},
-    trie_id::trie_id,
-    trie_reader::{new_trie_reader, TrieReader},
-    types::{
-        Database, Hash, Id, MissingNodeError, Tracer, ADDRESS_LENGTH, EMPTY_ROOT_HASH, HASH_LENGTH,
-    },
+    node::{decode_node, FullNode, HashNode, Node, NodeFlag, ShortNode},
+    trie_reader::{new_empty_reader, new_trie_reader, TrieReader},
+    types::{Database, Hash, Id, MissingNodeError, Tracer, EMPTY_ROOT_HASH, HASH_LENGTH},
 };
 
 pub struct Trie {
@@ -34,268 +29,368 @@ pub struct Trie {
 impl std::error::Error for MissingNodeError {}
 
 impl Trie {
-    pub fn get(
+    /// Returns the value stored in the trie for key, or `None` if the key is
+    /// not present.
+    pub fn get(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>, ()> {
+        // The trie is unusable after a commit.
+        if self.committed == Some(true) {
+            return Err(());
+        }
+
+        let root = self.root.clone().unwrap_or(Node::Empty);
+        let (value, new_root, did_resolve) = self.get_at(root, &keybytes_to_hex(key), 0)?;
+        if did_resolve {
+            self.root = Some(new_root);
+        }
+
+        Ok(value)
+    }
+
+    /// Associates key with value in the trie. If value has length zero, any
+    /// existing value is deleted from the trie.
+    ///
+    /// If a node can't be resolved the trie is left unchanged.
+    pub fn update(&mut self, key: &[u8], value: &[u8]) -> Result<(), ()> {
+        // The trie is unusable after a commit.
+        if self.committed == Some(true) {
+            return Err(());
+        }
+
+        let root = self.root.clone().unwrap_or(Node::Empty);
+        let key = keybytes_to_hex(key);
+
+        let (_, new_root) = if value.is_empty() {
+            self.delete_at(root, Vec::new(), &key)?
+        } else {
+            self.insert(root, Vec::new(), &key, Node::ValueNode(value.to_vec()))?
+        };
+        self.unhashed = Some(self.unhashed.unwrap_or(0) + 1);
+        self.set_root(new_root);
+
+        Ok(())
+    }
+
+    /// Removes any existing value for key from the trie.
+    ///
+    /// If a node can't be resolved the trie is left unchanged.
+    pub fn delete(&mut self, key: &[u8]) -> Result<(), ()> {
+        // The trie is unusable after a commit.
+        if self.committed == Some(true) {
+            return Err(());
+        }
+
+        let root = self.root.clone().unwrap_or(Node::Empty);
+
+        let (_, new_root) = self.delete_at(root, Vec::new(), &keybytes_to_hex(key))?;
+        self.unhashed = Some(self.unhashed.unwrap_or(0) + 1);
+        self.set_root(new_root);
+
+        Ok(())
+    }
+
+    fn set_root(&mut self, root: Node) {
+        self.root = match root {
+            Node::Empty => None,
+            root => Some(root),
+        };
+    }
+
+    fn get_at(
         &mut self,
         orig_node: Node,
-        key: Vec<u8>,
+        key: &[u8],
         pos: usize,
-    ) -> (Vec<u8>, Node, bool, Option<String>) {
+    ) -> Result<(Option<Vec<u8>>, Node, bool), ()> {
         match orig_node {
-            Node::FullNode(node) => {
-                let req_pos = key[pos];
-                let (value, newnode, did_resolve, err) =
-                    self.get(node.children[req_pos as usize].clone(), key, pos + 1);
+            Node::Empty => Ok((None, Node::Empty, false)),
+            Node::ValueNode(val) => Ok((Some(val.clone()), Node::ValueNode(val), false)),
+            Node::ShortNode(mut n) => {
+                if key.len() - pos < n.key.len() || n.key[..] != key[pos..pos + n.key.len()] {
+                    // key not found in trie
+                    return Ok((None, Node::ShortNode(n), false));
+                }
+                let (value, new_node, did_resolve) =
+                    self.get_at(n.val.as_ref().clone(), key, pos + n.key.len())?;
+                if did_resolve {
+                    n.val = Box::new(new_node);
+                }
 
-                return (value, newnode, false, None);
+                Ok((value, Node::ShortNode(n), did_resolve))
             }
-            Node::ValueNode(val) => {
-                return (val.clone(), Node::ValueNode(val), false, None);
-            }
-            Node::HashNode(e) => {
-                let new_node = self.resolve_and_track(e, Some(key.clone())).unwrap();
-
-                let (value, newnode, did_resolve, err) = self.get(new_node, key, pos);
+            Node::FullNode(mut n) => {
+                let index = key[pos] as usize;
+                let (value, new_node, did_resolve) =
+                    self.get_at(n.children[index].clone(), key, pos + 1)?;
+                if did_resolve {
+                    n.children[index] = new_node;
+                }
 
-                return (value, newnode, did_resolve, err);
+                Ok((value, Node::FullNode(n), did_resolve))
             }
-            Node::ShortNode(e) => {
-                let (value, newnode, did_resolve, err) = self.get(e.val.as_ref().clone(), key, pos);
+            Node::HashNode(hash) => {
+                let child = self.resolve_and_track(hash, Some(key[..pos].to_vec()))?;
+                let (value, new_node, _) = self.get_at(child, key, pos)?;
 
-                return (value, newnode, did_resolve, err);
+                Ok((value, new_node, true))
             }
-            _ => panic!("sds"),
         }
     }
 
-    pub fn insert(
+    fn insert(
         &mut self,
         node: Node,
         prefix: Vec<u8>,
-        key: Vec<u8>,
+        key: &[u8],
         value: Node,
     ) -> Result<(bool, Node), ()> {
-        if key.len() == 0 {
-            if let Node::ValueNode(v) = node {
-                if let Node::ValueNode(n) = &value {
-                    let comp = v == *n;
-
-                    return Ok((!comp, value));
-                }
+        if key.is_empty() {
+            if let (Node::ValueNode(v), Node::ValueNode(n)) = (&node, &value) {
+                return Ok((v != n, value));
             }
 
             return Ok((true, value));
         }
 
         match node {
-            Node::FullNode(mut n) => {
-                let index = key[0] as usize;
-                let ins = self.insert(n.children[index].clone(), prefix, key, value);
-
-                if let Ok((dirty, nn)) = ins {
-                    if !dirty {
-                        return Err(());
-                    }
-                    n.flags = self.new_flag();
-                    n.children[index] = nn;
-
-                    return Ok((true, Node::FullNode(n)));
-                } else {
-                    return Err(());
-                }
-            }
-            Node::HashNode(n) => {
-                // We've hit a part of the trie that isn't loaded yet. Load
-                // the node and insert into it. This leaves all child nodes on
-                // the path to the value in the trie.
-                let rt = self.resolve_and_track(n, Some(prefix.clone()));
-                if let Ok(rt) = rt {
-                    let ins = self.insert(rt, prefix, key, value);
-                    if let Ok((dirty, nn)) = ins {
-                        return Ok((true, nn));
-                    } else {
-                        return Err(());
-                    }
-                } else {
-                    return Err(());
-                }
-            }
             Node::ShortNode(n) => {
-                let matchlen = prefix_len(&key, &n.key);
+                let matchlen = prefix_len(key, &n.key);
 
                 // If the whole key matches, keep this short node as is
                 // and only update the value.
                 if matchlen == n.key.len() {
-                    let ins = self.insert(n.val.as_ref().clone(), prefix, key, value);
-
-                    if let Ok(ins) = ins {
-                        if !ins.0 {
-                            return Err(());
-                        } else {
-                            return Ok((true, Node::ShortNode(n)));
-                        }
-                    } else {
-                        return Err(());
+                    let (dirty, nn) = self.insert(
+                        n.val.as_ref().clone(),
+                        [prefix.as_slice(), &key[..matchlen]].concat(),
+                        &key[matchlen..],
+                        value,
+                    )?;
+                    if !dirty {
+                        return Ok((false, Node::ShortNode(n)));
                     }
+
+                    return Ok((
+                        true,
+                        Node::ShortNode(ShortNode {
+                            key: n.key,
+                            val: Box::new(nn),
+                            flags: self.new_flag(),
+                        }),
+                    ));
                 }
 
+                // Otherwise branch out at the index where they differ.
                 let mut branch = FullNode {
                     flags: self.new_flag(),
                     ..Default::default()
                 };
 
-                let ins1 = self.insert(
+                let (_, nn) = self.insert(
                     Node::Empty,
-                    [prefix.clone(), n.key[..matchlen + 1].to_vec()].concat(),
-                    n.key.clone(),
+                    [prefix.as_slice(), &n.key[..matchlen + 1]].concat(),
+                    &n.key[matchlen + 1..],
                     n.val.as_ref().clone(),
-                );
-
-                if let Ok((_, nn)) = ins1 {
-                    branch.children[n.key[matchlen] as usize] = nn;
-                } else {
-                    return Err(());
-                }
+                )?;
+                branch.children[n.key[matchlen] as usize] = nn;
 
-                let ins2 = self.insert(
+                let (_, nn) = self.insert(
                     Node::Empty,
-                    [prefix, key[..matchlen + 1].to_vec()].concat(),
-                    n.key,
-                    n.val.as_ref().clone(),
-                );
-
-                if let Ok((_, nn)) = ins2 {
-                    branch.children[key[matchlen] as usize] = nn;
-                } else {
-                    return Err(());
-                }
+                    [prefix.as_slice(), &key[..matchlen + 1]].concat(),
+                    &key[matchlen + 1..],
+                    value,
+                )?;
+                branch.children[key[matchlen] as usize] = nn;
 
                 // Replace this shortNode with the branch if it occurs at index 0.
                 if matchlen == 0 {
                     return Ok((true, Node::FullNode(branch)));
                 }
 
-                // New branch node is created as a child of the original short node.
-                // Track the newly inserted node in the tracer. The node identifier
-                // passed is the path from the root node.
-                //t.tracer.onInsert(append(prefix, key[:matchlen]...))
-
                 // Replace it with a short node leading up to the branch.
-                return Ok((
+                Ok((
                     true,
                     Node::ShortNode(ShortNode {
                         flags: self.new_flag(),
                         key: key[..matchlen].to_vec(),
                         val: Box::new(Node::FullNode(branch)),
                     }),
-                ));
-            }
-            Node::Empty => {
-                return Ok((
-                    true,
-                    Node::ShortNode(ShortNode {
-                        flags: self.new_flag(),
-                        key,
-                        val: Box::new(value),
-                    }),
                 ))
             }
-            Node::ValueNode(_) => {
-                panic!("PANIICCC I DONT KNOW WHY");
-            }
-        }
-    }
+            Node::FullNode(mut n) => {
+                let index = key[0] as usize;
+                let (dirty, nn) = self.insert(
+                    n.children[index].clone(),
+                    [prefix.as_slice(), &key[..1]].concat(),
+                    &key[1..],
+                    value,
+                )?;
+                if !dirty {
+                    return Ok((false, Node::FullNode(n)));
+                }
+                n.flags = self.new_flag();
+                n.children[index] = nn;
 
-    /// delete returns the new root of the trie with key deleted.
-    /// It reduces the trie to minimal form by simplifying
-    /// nodes on the way up after deleting recursively.
-    pub fn delete(
-        &mut self,
-        node: Node,
-        prefix: Vec<u8>,
-        key: Vec<u8>,
-    ) -> Result<(bool, Node), ()> {
-        match node {
-            Node::FullNode(n) => {
-                let del = self.delete(
-                    n.children[key[0] as usize].clone(),
-                    [prefix, key[0].to_ne_bytes().to_vec()].concat(),
-                    key[1..].to_vec(),
-                );
+                Ok((true, Node::FullNode(n)))
             }
+            Node::Empty => Ok((
+                true,
+                Node::ShortNode(ShortNode {
+                    flags: self.new_flag(),
+                    key: key.to_vec(),
+                    val: Box::new(value),
+                }),
+            )),
             Node::HashNode(n) => {
                 // We've hit a part of the trie that isn't loaded yet. Load
                 // the node and insert into it. This leaves all child nodes on
                 // the path to the value in the trie.
-                let rn = self.resolve_and_track(n, Some(prefix.clone()));
-
-                if let Ok(rn) = rn {
-                    let ins = self.delete(rn.clone(), prefix, key);
-                    if let Ok((dirty, nn)) = ins {
-                        if !dirty {
-                            return Err(());
-                        }
-
-                        return Ok((false, rn));
-                    }
-                } else {
-                    return Err(());
+                let rn = self.resolve_and_track(n, Some(prefix.clone()))?;
+                let (dirty, nn) = self.insert(rn.clone(), prefix, key, value)?;
+                if !dirty {
+                    return Ok((false, rn));
                 }
+
+                Ok((true, nn))
             }
+            Node::ValueNode(_) => Err(()),
+        }
+    }
+
+    /// Returns the new root of the trie with key deleted.
+    /// It reduces the trie to minimal form by simplifying
+    /// nodes on the way up after deleting recursively.
+    fn delete_at(&mut self, node: Node, prefix: Vec<u8>, key: &[u8]) -> Result<(bool, Node), ()> {
+        match node {
             Node::ShortNode(n) => {
-                let matchlen = prefix_len(&key, &n.key);
+                let matchlen = prefix_len(key, &n.key);
                 if matchlen < n.key.len() {
-                    return Err(());
+                    // don't replace n on mismatch
+                    return Ok((false, Node::ShortNode(n)));
                 }
-
                 if matchlen == key.len() {
-                    return Ok((true, (Node::Empty)));
+                    // remove n entirely for whole matches
+                    return Ok((true, Node::Empty));
                 }
 
                 // The key is longer than n.Key. Remove the remaining suffix
                 // from the subtrie. Child can never be nil here since the
                 // subtrie must contain at least two other values with keys
                 // longer than n.Key.
+                let (dirty, child) = self.delete_at(
+                    n.val.as_ref().clone(),
+                    [prefix.as_slice(), &key[..n.key.len()]].concat(),
+                    &key[n.key.len()..],
+                )?;
+                if !dirty {
+                    return Ok((false, Node::ShortNode(n)));
+                }
 
-                let del = self.delete(n.val.as_ref().clone(), prefix, key);
+                match child {
+                    // Deleting from the subtrie reduced it to another
+                    // short node. Merge the nodes to avoid creating a
+                    // shortNode{..., shortNode{...}}.
+                    Node::ShortNode(child) => Ok((
+                        true,
+                        Node::ShortNode(ShortNode {
+                            flags: self.new_flag(),
+                            key: [n.key, child.key].concat(),
+                            val: child.val,
+                        }),
+                    )),
+                    child => Ok((
+                        true,
+                        Node::ShortNode(ShortNode {
+                            flags: self.new_flag(),
+                            key: n.key,
+                            val: Box::new(child),
+                        }),
+                    )),
+                }
+            }
+            Node::FullNode(mut n) => {
+                let index = key[0] as usize;
+                let (dirty, nn) = self.delete_at(
+                    n.children[index].clone(),
+                    [prefix.as_slice(), &key[..1]].concat(),
+                    &key[1..],
+                )?;
+                if !dirty {
+                    return Ok((false, Node::FullNode(n)));
+                }
+                n.flags = self.new_flag();
+                n.children[index] = nn;
+
+                // Because n is a full node, it must've contained at least two children
+                // before the delete operation. If the new child value is non-nil, n still
+                // has at least two children after the deletion, and cannot be reduced to
+                // a short node.
+                if !matches!(n.children[index], Node::Empty) {
+                    return Ok((true, Node::FullNode(n)));
+                }
 
-                if let Ok((dirty, child)) = del {
-                    if !dirty {
-                        return Err(());
-                    }
+                // Reduction:
+                // Check how many non-nil entries are left after deleting and
+                // reduce the full node to a short node if only one entry is
+                // left.
+                let mut remaining = n
+                    .children
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, child)| !matches!(child, Node::Empty));
+                let pos = match (remaining.next(), remaining.next()) {
+                    (Some((pos, _)), None) => pos,
+                    // n still contains at least two values and cannot be reduced.
+                    _ => return Ok((true, Node::FullNode(n))),
+                };
 
-                    match child {
-                        Node::ShortNode(child_n) => {
-                            return Ok((
-                                true,
-                                Node::ShortNode(ShortNode {
-                                    flags: self.new_flag(),
-                                    key: [n.key, child_n.key].concat(),
-                                    val: child_n.val,
-                                }),
-                            ));
-                        }
-                        _ => {
-                            return Ok((
-                                true,
-                                Node::ShortNode(ShortNode {
-                                    flags: self.new_flag(),
-                                    val: Box::new(child),
-                                    key: n.key,
-                                }),
-                            ));
-                        }
+                if pos != 16 {
+                    // If the remaining entry is a short node, it replaces
+                    // n and its key gets the missing nibble tacked to the
+                    // front. This avoids creating an invalid
+                    // shortNode{..., shortNode{...}}. Since the entry
+                    // might not be loaded yet, resolve it just for this
+                    // check.
+                    let cnode = self.resolve(
+                        n.children[pos].clone(),
+                        [prefix.as_slice(), &[pos as u8]].concat(),
+                    )?;
+                    if let Node::ShortNode(cnode) = cnode {
+                        return Ok((
+                            true,
+                            Node::ShortNode(ShortNode {
+                                flags: self.new_flag(),
+                                key: [&[pos as u8], cnode.key.as_slice()].concat(),
+                                val: cnode.val,
+                            }),
+                        ));
                     }
-                } else {
-                    return Err(());
                 }
+
+                // Otherwise, n is replaced by a one-nibble short node
+                // containing the child.
+                Ok((
+                    true,
+                    Node::ShortNode(ShortNode {
+                        flags: self.new_flag(),
+                        key: vec![pos as u8],
+                        val: Box::new(n.children[pos].clone()),
+                    }),
+                ))
             }
-            Node::ValueNode(n) => {
-                return Ok((true, Node::Empty));
+            Node::ValueNode(_) => Ok((true, Node::Empty)),
+            Node::Empty => Ok((false, Node::Empty)),
+            Node::HashNode(n) => {
+                // We've hit a part of the trie that isn't loaded yet. Load
+                // the node and delete from it. This leaves all child nodes on
+                // the path to the value in the trie.
+                let rn = self.resolve_and_track(n, Some(prefix.clone()))?;
+                let (dirty, nn) = self.delete_at(rn.clone(), prefix, key)?;
+                if !dirty {
+                    return Ok((false, rn));
+                }
+
+                Ok((true, nn))
             }
-            Node::Empty => return Err(()),
         }
-
-        Err(())
     }
 
     pub fn resolve(&mut self, node: Node, prefix: Vec<u8>) -> Result<Node, ()> {
@@ -316,25 +411,23 @@ impl Trie {
         hash_node: HashNode,
         prefix: Option<Vec<u8>>,
     ) -> Result<Node, ()> {
-        let mut hash: Hash = [0; HASH_LENGTH];
-        hash.copy_from_slice(&hash_node[..HASH_LENGTH]);
-
+        let hash: Hash = hash_node.as_slice().try_into().map_err(|_| ())?;
         let blob = self.reader.node(prefix, hash)?;
 
-        Ok(must_decode_node(hash_node, blob))
+        decode_node(Some(hash_node), &blob)
     }
 
     pub fn new_flag(&self) -> NodeFlag {
-        return NodeFlag {
+        NodeFlag {
             dirty: true,
             hash: None,
-        };
+        }
     }
 
     /// Calculates the root hash of the given trie.
     fn hash_root(&self) -> (Node, Option<Node>) {
         if self.root.is_none() {
-            return (Node::HashNode(Vec::new()), None);
+            return (Node::HashNode(EMPTY_ROOT_HASH.to_vec()), None);
         }
 
         let mut hasher = Hasher::new();
@@ -378,7 +471,7 @@ impl Trie {
 ///
 /// Returns a Result containing either the new Trie instance or an error.
 pub fn new(id: Id, db: &impl Database) -> Result<Trie, ()> {
-    let reader = new_trie_reader(&id.state_root, &id.owner, db).unwrap();
+    let reader = new_trie_reader(&id.state_root, &id.owner, db).map_err(|_| ())?;
 
     let mut trie = Trie {
         owner: id.owner,
@@ -398,6 +491,173 @@ pub fn new(id: Id, db: &impl Database) -> Result<Trie, ()> {
 }
 
 // NewEmpty is a shortcut to create empty tree. It's mostly used in tests.
-pub fn new_empty(db: &impl Database) -> Trie {
-    new(trie_id(EMPTY_ROOT_HASH), db).unwrap()
+pub fn new_empty() -> Trie {
+    Trie {
+        owner: [0; HASH_LENGTH],
+        reader: new_empty_reader(),
+        tracer: Tracer::default(),
+        root: None,
+        committed: None,
+        unhashed: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        database::MemoryDatabase,
+        trie::{trie_id::trie_id, types::Reader},
+        utils::to_hex,
+    };
+
+    fn root_of(pairs: &[(&str, &str)]) -> String {
+        let mut trie = new_empty();
+        for (key, value) in pairs {
+            trie.update(key.as_bytes(), value.as_bytes()).unwrap();
+        }
+        to_hex(&trie.hash())
+    }
+
+    #[test]
+    fn empty_root() {
+        assert_eq!(new_empty().hash(), EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn insert_root() {
+        assert_eq!(
+            root_of(&[
+                ("doe", "reindeer"),
+                ("dog", "puppy"),
+                ("dogglesworth", "cat")
+            ]),
+            "8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3"
+        );
+        assert_eq!(
+            root_of(&[("A", &"a".repeat(50))]),
+            "d23786fb4a010da3ce639d66d5e904a11dbc02746d1ce25029e53290cabf28ab"
+        );
+    }
+
+    #[test]
+    fn delete_root() {
+        // Empty values delete their key.
+        let pairs = [
+            ("do", "verb"),
+            ("ether", "wookiedoo"),
+            ("horse", "stallion"),
+            ("shaman", "horse"),
+            ("doge", "coin"),
+            ("ether", ""),
+            ("dog", "puppy"),
+            ("shaman", ""),
+        ];
+        assert_eq!(
+            root_of(&pairs),
+            "5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84"
+        );
+
+        let mut trie = new_empty();
+        trie.update(b"dog", b"puppy").unwrap();
+        trie.update(b"doge", b"coin").unwrap();
+        trie.delete(b"doge").unwrap();
+        trie.delete(b"dog").unwrap();
+        assert_eq!(trie.hash(), EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn get() {
+        let mut trie = new_empty();
+        trie.update(b"doe", b"reindeer").unwrap();
+        trie.update(b"dog", b"puppy").unwrap();
+        trie.update(b"dogglesworth", b"cat").unwrap();
+
+        assert_eq!(trie.get(b"dog").unwrap(), Some(b"puppy".to_vec()));
+        assert_eq!(trie.get(b"dogglesworth").unwrap(), Some(b"cat".to_vec()));
+        assert_eq!(trie.get(b"do").unwrap(), None);
+        assert_eq!(trie.get(b"unknown").unwrap(), None);
+    }
+
+    #[test]
+    fn get_from_database() {
+        let db = MemoryDatabase::new();
+        let mut trie = new_empty();
+        for i in 0u32..100 {
+            trie.update(&i.to_be_bytes(), &[i as u8 + 1; 40]).unwrap();
+        }
+        let (root, nodes) = trie.commit();
+        db.update(&nodes.unwrap());
+
+        let mut trie = new(trie_id(root), &db).unwrap();
+        for i in 0u32..100 {
+            assert_eq!(
+                trie.get(&i.to_be_bytes()).unwrap(),
+                Some(vec![i as u8 + 1; 40])
+            );
+        }
+        assert_eq!(trie.get(&[1; 4]).unwrap(), None);
+    }
+
+    /// CorruptDatabase returns root for the root node, and blob for every
+    /// other node.
+    #[derive(Clone)]
+    struct CorruptDatabase {
+        root: Vec<u8>,
+        blob: Vec<u8>,
+    }
+
+    impl Reader for CorruptDatabase {
+        fn node(&self, _: Hash, path: Option<Vec<u8>>, _: Hash) -> Result<Vec<u8>, ()> {
+            match path {
+                Some(path) if !path.is_empty() => Ok(self.blob.clone()),
+                _ => Ok(self.root.clone()),
+            }
+        }
+    }
+
+    impl Database for CorruptDatabase {
+        fn reader(&self, _: &Hash) -> Result<Box<dyn Reader>, std::io::Error> {
+            Ok(Box::new(self.clone()))
+        }
+    }
+
+    #[test]
+    fn corrupt_node() {
+        // A full node with a single hashed child at slot 0.
+        let mut root = vec![0xf1, 0xa0];
+        root.extend_from_slice(&[1; HASH_LENGTH]);
+        root.extend_from_slice(&[0x80; 16]);
+
+        // A list of one item is neither a short nor a full node, and 0xff is
+        // not valid RLP.
+        for blob in [vec![0xc1, 0x01], vec![0xff], vec![0xc2, 0x80]] {
+            let db = CorruptDatabase {
+                root: root.clone(),
+                blob: blob.clone(),
+            };
+            let mut trie = new(trie_id([1; HASH_LENGTH]), &db).unwrap();
+            let root_hash = trie.hash();
+            let unhashed = trie.unhashed;
+            assert_eq!(trie.get(&[0x01]), Err(()));
+            assert_eq!(trie.update(&[0x01], b"puppy"), Err(()));
+            assert_eq!(trie.delete(&[0x01]), Err(()));
+            // The failed writes leave the trie as it was.
+            assert_eq!(trie.unhashed, unhashed);
+            assert_eq!(trie.hash(), root_hash);
+            assert_eq!(trie.get(&[0x01]), Err(()));
+            assert_eq!(trie.get(&[0x10]).unwrap(), None);
+
+            let db = CorruptDatabase {
+                root: blob.clone(),
+                blob,
+            };
+            assert!(new(trie_id([1; HASH_LENGTH]), &db).is_err());
+        }
+
+        let mut trie = new_empty();
+        assert!(trie
+            .resolve(Node::HashNode(vec![1, 2, 3]), Vec::new())
+            .is_err());
+    }
 }
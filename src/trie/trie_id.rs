@@ -10,3 +10,13 @@ pub fn trie_id(root: Hash) -> Id {
         root,
     }
 }
+
+// StorageTrieID constructs an identifier for storage trie which belongs to a certain
+// state and contract specified by the stateRoot and owner.
+pub fn storage_trie_id(state_root: Hash, owner: Hash, root: Hash) -> Id {
+    Id {
+        state_root,
+        owner,
+        root,
+    }
+}
@@ -1,5 +1,3 @@
-use std::collections::HashMap;
-
 use super::types::{Database, Hash, MissingNodeError, Reader, EMPTY_ROOT_HASH, HASH_LENGTH};
 
 /// A wrapper of the underlying node reader. Not safe for concurrent usage.
@@ -21,13 +19,13 @@ impl TrieReader {
             }
 
             let blob = blob.unwrap();
-            if blob.len() == 0 {
+            if blob.is_empty() {
                 return Err(());
             }
 
-            return Ok(blob);
+            Ok(blob)
         } else {
-            return Err(());
+            Err(())
         }
     }
 }
@@ -38,16 +36,13 @@ pub fn new_trie_reader(
     db: &impl Database,
 ) -> Result<TrieReader, MissingNodeError> {
     if state_root == &[0; HASH_LENGTH] || state_root == &EMPTY_ROOT_HASH {
-        if state_root == &[0; HASH_LENGTH] {
-            eprint!("Zero state root hash!");
-        }
         return Ok(TrieReader {
             owner: *owner,
             reader: None,
         });
     }
 
-    match db.reader(&state_root) {
+    match db.reader(state_root) {
         Ok(reader) => Ok(TrieReader {
             owner: *owner,
             reader: Some(reader),
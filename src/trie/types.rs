@@ -1,19 +1,26 @@
-use std::{collections::HashMap, fmt};
+use std::collections::{BTreeMap, HashMap};
 
 pub const HASH_LENGTH: usize = 32;
 
-pub const ADDRESS_LENGTH: u32 = 20;
+pub const ADDRESS_LENGTH: usize = 20;
 
 // Hash represents the 32 byte Keccak256 hash of arbitrary data.
 pub type Hash = [u8; HASH_LENGTH];
 
-pub const EMPTY_ROOT_HASH: Hash = [0; HASH_LENGTH];
+// Address represents the 20 byte address of an Ethereum account.
+pub type Address = [u8; ADDRESS_LENGTH];
+
+// EmptyRootHash is the known root hash of an empty merkle trie.
+pub const EMPTY_ROOT_HASH: Hash = [
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+];
 
 #[derive(Default, Debug)]
 pub struct Tracer {
-    inserts: HashMap<String, ()>,
-    deletes: HashMap<String, ()>,
-    access_list: HashMap<String, Vec<u8>>,
+    pub inserts: HashMap<String, ()>,
+    pub deletes: HashMap<String, ()>,
+    pub access_list: HashMap<String, Vec<u8>>,
 }
 
 pub trait Reader {
@@ -50,3 +57,18 @@ impl std::fmt::Display for MissingNodeError {
         )
     }
 }
+
+/// A dirty trie node collected by a commit, in its RLP encoding.
+#[derive(Debug, Clone)]
+pub struct TrieNode {
+    pub hash: Hash,
+    pub blob: Vec<u8>,
+}
+
+/// NodeSet contains the dirty nodes of a single trie collected by a commit,
+/// keyed by their path from the root.
+#[derive(Debug, Clone, Default)]
+pub struct NodeSet {
+    pub owner: Hash,
+    pub nodes: BTreeMap<Vec<u8>, TrieNode>,
+}
@@ -0,0 +1,96 @@
+use crate::{
+    rlp::{RlpDecodable, RlpEncodable},
+    trie::types::{Hash, EMPTY_ROOT_HASH},
+};
+
+use super::u256::U256;
+
+// EmptyCodeHash is the known hash of the empty EVM bytecode.
+pub const EMPTY_CODE_HASH: Hash = [
+    0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+    0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70,
+];
+
+/// StateAccount is the Ethereum consensus representation of accounts.
+/// These objects are stored in the main account trie, encoded as the RLP list
+/// `[nonce, balance, storage_root, code_hash]`.
+#[derive(Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct StateAccount {
+    pub nonce: u64,
+    pub balance: U256,
+    pub storage_root: Hash, // merkle root of the storage trie
+    pub code_hash: Hash,
+}
+
+impl Default for StateAccount {
+    fn default() -> Self {
+        Self {
+            nonce: 0,
+            balance: U256::ZERO,
+            storage_root: EMPTY_ROOT_HASH,
+            code_hash: EMPTY_CODE_HASH,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        rlp::{decode, encode},
+        trie::types::HASH_LENGTH,
+        utils::from_hex,
+    };
+
+    #[test]
+    fn empty_account_rlp() {
+        let blob = from_hex(
+            "f8448080a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a0c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+        )
+        .unwrap();
+        assert_eq!(encode(&StateAccount::default()), blob);
+        assert_eq!(decode(&blob), Ok(StateAccount::default()));
+    }
+
+    #[test]
+    fn account_rlp() {
+        let account = StateAccount {
+            nonce: 0x0102,
+            balance: U256::from(1_000_000_000_000_000_000u128),
+            storage_root: [0x11; HASH_LENGTH],
+            code_hash: [0x22; HASH_LENGTH],
+        };
+        let blob = encode(&account);
+        let expected = [
+            from_hex("f84e820102880de0b6b3a7640000a0").unwrap(),
+            vec![0x11; HASH_LENGTH],
+            vec![0xa0],
+            vec![0x22; HASH_LENGTH],
+        ]
+        .concat();
+        assert_eq!(blob, expected);
+        assert_eq!(decode(&blob), Ok(account));
+    }
+
+    #[test]
+    fn invalid_rlp() {
+        let hashes = [[0xa0].as_slice(), &[0; 32], &[0xa0], &[0; 32]].concat();
+        let valid = [&[0xf8, 0x44, 0x80, 0x80], hashes.as_slice()].concat();
+        assert!(decode::<StateAccount>(&valid).is_ok());
+
+        // Trailing bytes after the list.
+        assert!(decode::<StateAccount>(&[valid.as_slice(), &[0x80]].concat()).is_err());
+        // Leading zero in the nonce.
+        let blob = [&[0xf8, 0x45, 0x81, 0x00, 0x80], hashes.as_slice()].concat();
+        assert!(decode::<StateAccount>(&blob).is_err());
+        // Missing code hash.
+        let blob = [&[0xe3, 0x80, 0x80], &hashes[..33]].concat();
+        assert!(decode::<StateAccount>(&blob).is_err());
+        // Short storage root.
+        let blob = [0xc6, 0x80, 0x80, 0x81, 0x01, 0x81, 0x01];
+        assert!(decode::<StateAccount>(&blob).is_err());
+        // Balance larger than 256 bits.
+        let blob = [&[0xf8, 0x65, 0x80, 0xa1], &[1; 33][..], hashes.as_slice()].concat();
+        assert!(decode::<StateAccount>(&blob).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use std::{fmt, ops::BitOrAssign};
+
+use crate::{
+    rlp::{RlpDecodable, RlpEncodable},
+    utils::{keccak256, to_hex},
+};
+
+use super::receipt::{Log, Receipt};
+
+// BloomByteLength represents the number of bytes used in a header log bloom.
+pub const BLOOM_BYTE_LENGTH: usize = 256;
+
+// BloomBitLength represents the number of bits used in a header log bloom.
+pub const BLOOM_BIT_LENGTH: usize = 8 * BLOOM_BYTE_LENGTH;
+
+/// Bloom represents a 2048 bit bloom filter over the addresses and topics of
+/// logs. Every value sets three bits, chosen from its Keccak-256 hash.
+#[derive(Clone, Copy, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[rlp(transparent)]
+pub struct Bloom(pub [u8; BLOOM_BYTE_LENGTH]);
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom([0; BLOOM_BYTE_LENGTH])
+    }
+}
+
+impl fmt::Debug for Bloom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Bloom(0x{})", to_hex(&self.0))
+    }
+}
+
+/// Returns the byte indexes and bit masks of the three bits set for data.
+fn bloom_values(data: &[u8]) -> [(usize, u8); 3] {
+    let h = keccak256(data);
+    [0, 2, 4].map(|i| {
+        // The low 11 bits of each of the first three pairs of bytes select a
+        // bit, counted from the end of the filter.
+        let v = (usize::from(h[i]) << 8 | usize::from(h[i + 1])) & (BLOOM_BIT_LENGTH - 1);
+        (BLOOM_BYTE_LENGTH - 1 - v / 8, 1 << (v % 8))
+    })
+}
+
+impl Bloom {
+    /// Adds data to the filter.
+    pub fn accrue(&mut self, data: &[u8]) {
+        for (i, mask) in bloom_values(data) {
+            self.0[i] |= mask;
+        }
+    }
+
+    /// Adds the address and the topics of log to the filter.
+    pub fn accrue_log(&mut self, log: &Log) {
+        self.accrue(&log.address);
+        for topic in &log.topics {
+            self.accrue(topic);
+        }
+    }
+
+    /// Reports whether data may have been added to the filter. False
+    /// positives are possible, false negatives are not.
+    pub fn contains(&self, data: &[u8]) -> bool {
+        bloom_values(data)
+            .iter()
+            .all(|&(i, mask)| self.0[i] & mask == mask)
+    }
+}
+
+impl BitOrAssign for Bloom {
+    fn bitor_assign(&mut self, other: Self) {
+        for (a, b) in self.0.iter_mut().zip(other.0) {
+            *a |= b;
+        }
+    }
+}
+
+/// Returns the bloom filter of logs.
+pub fn logs_bloom(logs: &[Log]) -> Bloom {
+    let mut bloom = Bloom::default();
+    for log in logs {
+        bloom.accrue_log(log);
+    }
+    bloom
+}
+
+/// Returns the block level bloom filter, the union of the filters of
+/// receipts.
+pub fn create_bloom(receipts: &[Receipt]) -> Bloom {
+    let mut bloom = Bloom::default();
+    for receipt in receipts {
+        bloom |= receipt.bloom;
+    }
+    bloom
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        rlp::{decode, encode},
+        types::receipt::ReceiptStatus,
+    };
+
+    #[test]
+    fn contains() {
+        let mut bloom = Bloom::default();
+        for data in ["testtest", "test", "hallo", "other"] {
+            bloom.accrue(data.as_bytes());
+        }
+        for data in ["testtest", "test", "hallo", "other"] {
+            assert!(bloom.contains(data.as_bytes()), "{}", data);
+        }
+        for data in ["tes", "lo"] {
+            assert!(!bloom.contains(data.as_bytes()), "{}", data);
+        }
+    }
+
+    // Vector from geth's bloom9_test.go.
+    #[test]
+    fn accrue_many() {
+        let mut bloom = Bloom::default();
+        for i in 0..100 {
+            bloom.accrue(format!("xxxxxxxxxx data {} yyyyyyyyyyyyyy", i).as_bytes());
+        }
+        assert_eq!(
+            to_hex(&keccak256(&bloom.0)),
+            "c8d3ca65cdb4874300a9e39475508f23ed6da09fdbc487f89a2dcf50b09eb263"
+        );
+
+        let enc = encode(&bloom);
+        assert_eq!(to_hex(&enc[..3]), "b90100");
+        assert_eq!(decode::<Bloom>(&enc).unwrap(), bloom);
+    }
+
+    #[test]
+    fn bloom_of_receipts() {
+        let log = |address: u8, topic: u8| Log {
+            address: [address; 20],
+            topics: vec![[topic; 32]],
+            data: Vec::new(),
+        };
+        let a = Receipt::new(0, ReceiptStatus::Success, 21000, vec![log(1, 2)]);
+        let b = Receipt::new(2, ReceiptStatus::Success, 42000, vec![log(3, 4)]);
+        assert_eq!(a.bloom, logs_bloom(&a.logs));
+
+        let bloom = create_bloom(&[a, b]);
+        for data in [&[1u8; 20][..], &[2; 32], &[3; 20], &[4; 32]] {
+            assert!(bloom.contains(data));
+        }
+        assert_eq!(bloom, logs_bloom(&[log(1, 2), log(3, 4)]));
+        assert_eq!(create_bloom(&[]), Bloom::default());
+    }
+}
@@ -0,0 +1,124 @@
+use sha2::{Digest, Sha256};
+
+use crate::{
+    rlp::{self, decode::append_uint64, Encodable},
+    trie::{new_empty, types::Hash},
+};
+
+use super::{receipt::Receipt, transaction::Transaction, withdrawal::Withdrawal};
+
+/// Computes the root of the trie mapping `rlp(i)` to the value of the i-th
+/// list element, as committed to by block headers.
+fn derive_sha(len: usize, value: impl Fn(usize) -> Vec<u8>) -> Hash {
+    let mut trie = new_empty();
+    for i in 0..len {
+        let key = append_uint64(Vec::new(), i as u64);
+        trie.update(&key, &value(i))
+            .expect("updating a trie without a database cannot fail");
+    }
+
+    trie.hash()
+}
+
+/// Returns the list root of items, using the RLP encoding of every item as
+/// its value.
+pub fn derive_list_root<T: Encodable>(items: &[T]) -> Hash {
+    derive_sha(items.len(), |i| rlp::encode(&items[i]))
+}
+
+/// Returns the transactions root of a block. Typed transactions are stored
+/// as their EIP-2718 envelope.
+pub fn transactions_root(txs: &[Transaction]) -> Hash {
+    derive_sha(txs.len(), |i| txs[i].encode_binary())
+}
+
+/// Returns the receipts root of a block. Typed receipts are stored with
+/// their type prefix.
+pub fn receipts_root(receipts: &[Receipt]) -> Hash {
+    derive_sha(receipts.len(), |i| receipts[i].encode_binary())
+}
+
+/// Returns the withdrawals root of a block, see EIP-4895.
+pub fn withdrawals_root(withdrawals: &[Withdrawal]) -> Hash {
+    derive_list_root(withdrawals)
+}
+
+/// Returns the requests hash of a block, the EIP-7685 commitment
+/// `sha256(sha256(requests_0) || sha256(requests_1) || ...)`. Every request
+/// is given as `request_type || request_data`, and requests without data are
+/// left out.
+pub fn requests_hash(requests: &[Vec<u8>]) -> Hash {
+    let mut hasher = Sha256::new();
+    for request in requests.iter().filter(|r| r.len() > 1) {
+        hasher.update(Sha256::digest(request));
+    }
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        trie::types::EMPTY_ROOT_HASH,
+        utils::{from_hex, keccak256, to_hex},
+    };
+
+    fn withdrawal(i: u64) -> Withdrawal {
+        Withdrawal {
+            index: i,
+            validator_index: i * 2,
+            address: [0x11; 20],
+            amount: 3,
+        }
+    }
+
+    #[test]
+    fn empty_lists() {
+        assert_eq!(transactions_root(&[]), EMPTY_ROOT_HASH);
+        assert_eq!(receipts_root(&[]), EMPTY_ROOT_HASH);
+        assert_eq!(withdrawals_root(&[]), EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn single_item() {
+        let w = Withdrawal {
+            index: 1,
+            validator_index: 2,
+            ..withdrawal(0)
+        };
+        let value = format!("d8010294{}03", "11".repeat(20));
+        assert_eq!(to_hex(&rlp::encode(&w)), value);
+
+        // The root is the hash of the leaf [compact(rlp(0)), value].
+        let leaf = from_hex(&format!("dd82208099{}", value)).unwrap();
+        assert_eq!(withdrawals_root(&[w]), keccak256(&leaf));
+    }
+
+    #[test]
+    fn insertion_order() {
+        let withdrawals: Vec<Withdrawal> = (0..300).map(withdrawal).collect();
+        let mut trie = new_empty();
+        for (i, w) in withdrawals.iter().enumerate().rev() {
+            let key = append_uint64(Vec::new(), i as u64);
+            trie.update(&key, &rlp::encode(w)).unwrap();
+        }
+        assert_eq!(withdrawals_root(&withdrawals), trie.hash());
+        assert_eq!(derive_list_root(&withdrawals), trie.hash());
+    }
+
+    #[test]
+    fn requests() {
+        let empty = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+        assert_eq!(to_hex(&requests_hash(&[])), empty);
+        assert_eq!(to_hex(&requests_hash(&[vec![0x00], vec![0x01]])), empty);
+
+        let mut deposits = vec![0x00];
+        deposits.extend_from_slice(&[0x01; 10]);
+        let mut consolidations = vec![0x02];
+        consolidations.extend_from_slice(&[0xAA; 3]);
+        assert_eq!(
+            to_hex(&requests_hash(&[deposits, vec![0x01], consolidations])),
+            "4c862c5fbcca31eb399a62610062149fab7d95caeafbd104ef64fd983820c988"
+        );
+    }
+}
@@ -0,0 +1,260 @@
+use crate::{
+    rlp::{self, RlpDecodable, RlpEncodable},
+    trie::types::{Address, Hash},
+    utils::keccak256,
+};
+
+use super::{
+    bloom::Bloom,
+    hashing::{transactions_root, withdrawals_root},
+    transaction::Transaction,
+    u256::U256,
+    withdrawal::Withdrawal,
+};
+
+// EmptyUncleHash is the known hash of the RLP encoding of an empty uncle
+// list.
+pub const EMPTY_UNCLE_HASH: Hash = [
+    0x1d, 0xcc, 0x4d, 0xe8, 0xde, 0xc7, 0x5d, 0x7a, 0xab, 0x85, 0xb5, 0x67, 0xb6, 0xcc, 0xd4, 0x1a,
+    0xd3, 0x12, 0x45, 0x1b, 0x94, 0x8a, 0x74, 0x13, 0xf0, 0xa1, 0x42, 0xfd, 0x40, 0xd4, 0x93, 0x47,
+];
+
+/// Header represents a block header in the Ethereum blockchain.
+///
+/// The optional fields were added by later forks and are only encoded up to
+/// the last one which is set, so headers of every fork round trip.
+#[derive(Debug, Clone, Default, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[rlp(trailing)]
+pub struct Header {
+    pub parent_hash: Hash,
+    pub uncle_hash: Hash,
+    pub coinbase: Address,
+    pub root: Hash,
+    pub tx_hash: Hash,
+    pub receipt_hash: Hash,
+    pub bloom: Bloom,
+    pub difficulty: U256,
+    pub number: u64,
+    pub gas_limit: u64,
+    pub gas_used: u64,
+    pub time: u64,
+    pub extra: Vec<u8>,
+    pub mix_digest: Hash,
+    pub nonce: [u8; 8],
+
+    // BaseFee was added by EIP-1559 and is ignored in legacy headers.
+    pub base_fee: Option<U256>,
+
+    // WithdrawalsHash was added by EIP-4895 and is ignored in legacy headers.
+    pub withdrawals_hash: Option<Hash>,
+
+    // BlobGasUsed was added by EIP-4844 and is ignored in legacy headers.
+    pub blob_gas_used: Option<u64>,
+
+    // ExcessBlobGas was added by EIP-4844 and is ignored in legacy headers.
+    pub excess_blob_gas: Option<u64>,
+
+    // ParentBeaconRoot was added by EIP-4788 and is ignored in legacy headers.
+    pub parent_beacon_root: Option<Hash>,
+
+    // RequestsHash was added by EIP-7685 and is ignored in legacy headers.
+    pub requests_hash: Option<Hash>,
+}
+
+impl Header {
+    /// Returns the block hash of the header, which is simply the keccak256
+    /// hash of its RLP encoding.
+    pub fn hash(&self) -> Hash {
+        keccak256(&rlp::encode(self))
+    }
+}
+
+/// BodyRoot names a header field which commits to a part of the block body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyRoot {
+    Transactions,
+    Uncles,
+    Withdrawals,
+}
+
+/// RootMismatch reports a header commitment which does not match the body.
+/// A `None` root means that the header has no such field, or that the body
+/// has no such part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RootMismatch {
+    pub root: BodyRoot,
+    pub header: Option<Hash>,
+    pub body: Option<Hash>,
+}
+
+/// Checks the transactions root, uncle hash and withdrawals root of header
+/// against the given block body, and returns all commitments which do not
+/// match. Withdrawals must be present exactly if the header has a
+/// withdrawals root.
+pub fn verify_body(
+    header: &Header,
+    txs: &[Transaction],
+    uncles: &[Header],
+    withdrawals: Option<&[Withdrawal]>,
+) -> Result<(), Vec<RootMismatch>> {
+    let checks = [
+        (
+            BodyRoot::Transactions,
+            Some(header.tx_hash),
+            Some(transactions_root(txs)),
+        ),
+        (
+            BodyRoot::Uncles,
+            Some(header.uncle_hash),
+            Some(keccak256(&rlp::encode(uncles))),
+        ),
+        (
+            BodyRoot::Withdrawals,
+            header.withdrawals_hash,
+            withdrawals.map(withdrawals_root),
+        ),
+    ];
+
+    let mismatches: Vec<RootMismatch> = checks
+        .into_iter()
+        .filter(|(_, header, body)| header != body)
+        .map(|(root, header, body)| RootMismatch { root, header, body })
+        .collect();
+    if !mismatches.is_empty() {
+        return Err(mismatches);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        rlp::RlpList,
+        trie::types::EMPTY_ROOT_HASH,
+        types::hashing::requests_hash,
+        utils::{from_hex, to_hex},
+    };
+
+    fn hash(hex: &str) -> Hash {
+        from_hex(hex).unwrap().try_into().unwrap()
+    }
+
+    fn mainnet_genesis() -> Header {
+        Header {
+            uncle_hash: EMPTY_UNCLE_HASH,
+            root: hash("d7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544"),
+            tx_hash: EMPTY_ROOT_HASH,
+            receipt_hash: EMPTY_ROOT_HASH,
+            difficulty: U256::from(0x400000000u64),
+            gas_limit: 5000,
+            extra: from_hex("11bbe8db4e347b4e8c937c1c8370e4b5ed33adb3db69cbdb7a38e1e50b1b82fa")
+                .unwrap(),
+            nonce: 0x42u64.to_be_bytes(),
+            ..Default::default()
+        }
+    }
+
+    /// Checks that header round trips with the given number of fields, and
+    /// returns its hash.
+    fn round_trip(header: &Header, fields: usize) -> Hash {
+        let enc = rlp::encode(header);
+        assert_eq!(RlpList::new(&enc).unwrap().len().unwrap(), fields);
+        assert_eq!(&rlp::decode::<Header>(&enc).unwrap(), header);
+        assert_eq!(header.hash(), keccak256(&enc));
+        header.hash()
+    }
+
+    #[test]
+    fn genesis_hash() {
+        let genesis = mainnet_genesis();
+        assert_eq!(
+            to_hex(&round_trip(&genesis, 15)),
+            "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+        );
+    }
+
+    #[test]
+    fn zero_fork_fields() {
+        let london = Header {
+            base_fee: Some(U256::ZERO),
+            ..mainnet_genesis()
+        };
+        let shanghai = Header {
+            withdrawals_hash: Some(EMPTY_ROOT_HASH),
+            ..london.clone()
+        };
+        let cancun = Header {
+            blob_gas_used: Some(0),
+            excess_blob_gas: Some(0),
+            parent_beacon_root: Some([0; 32]),
+            ..shanghai.clone()
+        };
+        let prague = Header {
+            requests_hash: Some(requests_hash(&[])),
+            ..cancun.clone()
+        };
+
+        // Zero valued fork fields are encoded, and change the hash.
+        let hashes = [
+            round_trip(&mainnet_genesis(), 15),
+            round_trip(&london, 16),
+            round_trip(&shanghai, 17),
+            round_trip(&cancun, 20),
+            round_trip(&prague, 21),
+        ];
+        for (i, a) in hashes.iter().enumerate() {
+            assert!(hashes[i + 1..].iter().all(|b| a != b));
+        }
+
+        let enc = rlp::encode(&cancun);
+        let list = RlpList::new(&enc).unwrap();
+        assert_eq!(list.get(15).unwrap().unwrap().as_bytes(), [0x80]);
+        assert_eq!(list.decode_at::<u64>(17).unwrap(), Some(0));
+    }
+
+    #[test]
+    fn gaps_encode_as_zero() {
+        // A Cancun field without a base fee encodes the base fee as zero.
+        let header = Header {
+            blob_gas_used: Some(0x20000),
+            ..mainnet_genesis()
+        };
+        let decoded = rlp::decode::<Header>(&rlp::encode(&header)).unwrap();
+        assert_eq!(decoded.base_fee, Some(U256::ZERO));
+        assert_eq!(decoded.withdrawals_hash, Some([0; 32]));
+        assert_eq!(decoded.blob_gas_used, Some(0x20000));
+        assert_eq!(decoded.excess_blob_gas, None);
+    }
+
+    #[test]
+    fn verify_bodies() {
+        let genesis = mainnet_genesis();
+        assert_eq!(verify_body(&genesis, &[], &[], None), Ok(()));
+
+        let shanghai = Header {
+            base_fee: Some(U256::from(7u64)),
+            withdrawals_hash: Some(EMPTY_ROOT_HASH),
+            ..genesis
+        };
+        assert_eq!(verify_body(&shanghai, &[], &[], Some(&[])), Ok(()));
+        let uncles = vec![mainnet_genesis()];
+        let mismatches = verify_body(&shanghai, &[], &uncles, None).unwrap_err();
+        assert_eq!(
+            mismatches,
+            [
+                RootMismatch {
+                    root: BodyRoot::Uncles,
+                    header: Some(EMPTY_UNCLE_HASH),
+                    body: Some(keccak256(&rlp::encode(&uncles))),
+                },
+                RootMismatch {
+                    root: BodyRoot::Withdrawals,
+                    header: Some(EMPTY_ROOT_HASH),
+                    body: None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,8 @@
+pub mod account;
+pub mod bloom;
+pub mod hashing;
+pub mod header;
+pub mod receipt;
+pub mod transaction;
+pub mod u256;
+pub mod withdrawal;
@@ -0,0 +1,281 @@
+use crate::{
+    rlp::{
+        self,
+        decode::{split, split_string, Kind},
+        list_len,
+        rlp_encoder::RlpEncoder,
+        string_len, Decodable, DecodeError, Encodable, RlpDecodable, RlpEncodable,
+    },
+    trie::types::{Address, Hash, HASH_LENGTH},
+};
+
+use super::{
+    bloom::{logs_bloom, Bloom},
+    transaction::LEGACY_TX_TYPE,
+};
+
+/// Log represents a contract log event. These events are generated by the
+/// LOG opcode and stored in the receipt of the transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct Log {
+    pub address: Address, // address of the contract that generated the event
+    pub topics: Vec<Hash>,
+    pub data: Vec<u8>,
+}
+
+/// ReceiptStatus is the first field of a receipt. Receipts before the
+/// Byzantium fork hold the intermediate state root instead of a status code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceiptStatus {
+    Failed,
+    Success,
+    PostState(Hash),
+}
+
+impl Encodable for ReceiptStatus {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        match self {
+            ReceiptStatus::Failed => rlp_enc.write_bytes(&[]),
+            ReceiptStatus::Success => rlp_enc.write_bytes(&[0x01]),
+            ReceiptStatus::PostState(root) => rlp_enc.write_bytes(root),
+        }
+    }
+
+    fn encoded_len(&self) -> usize {
+        match self {
+            ReceiptStatus::Failed | ReceiptStatus::Success => 1,
+            ReceiptStatus::PostState(_) => 1 + HASH_LENGTH,
+        }
+    }
+}
+
+impl Decodable<'_> for ReceiptStatus {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let (content, rest) = split_string(buf)?;
+        let status = match content {
+            [] => ReceiptStatus::Failed,
+            [0x01] => ReceiptStatus::Success,
+            root if root.len() == HASH_LENGTH => ReceiptStatus::PostState(root.try_into().unwrap()),
+            _ => return Err(DecodeError::InvalidValue(0)),
+        };
+        *buf = rest;
+
+        Ok(status)
+    }
+}
+
+/// Receipt represents the consensus fields of the result of a transaction.
+///
+/// Like transactions, typed receipts are encoded as `type || rlp(payload)`,
+/// see `encode_binary`, and wrapped in a byte string when they are part of an
+/// RLP list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Receipt {
+    pub tx_type: u8,
+    pub status: ReceiptStatus,
+    pub cumulative_gas_used: u64,
+    pub bloom: Bloom,
+    pub logs: Vec<Log>,
+}
+
+impl Receipt {
+    /// Creates a receipt, computing the bloom filter from logs.
+    pub fn new(
+        tx_type: u8,
+        status: ReceiptStatus,
+        cumulative_gas_used: u64,
+        logs: Vec<Log>,
+    ) -> Self {
+        Self {
+            tx_type,
+            status,
+            cumulative_gas_used,
+            bloom: logs_bloom(&logs),
+            logs,
+        }
+    }
+
+    fn encode_payload(&self, rlp_enc: &mut RlpEncoder) {
+        let offset = rlp_enc.list();
+        self.status.encode(rlp_enc);
+        self.cumulative_gas_used.encode(rlp_enc);
+        self.bloom.encode(rlp_enc);
+        self.logs.encode(rlp_enc);
+        rlp_enc.list_end(offset);
+    }
+
+    fn payload_len(&self) -> usize {
+        list_len(
+            self.status.encoded_len()
+                + self.cumulative_gas_used.encoded_len()
+                + self.bloom.encoded_len()
+                + self.logs.encoded_len(),
+        )
+    }
+
+    /// Returns the consensus encoding of the receipt, which is stored in the
+    /// receipt trie: the RLP list for legacy receipts, prefixed with the
+    /// transaction type for typed receipts.
+    pub fn encode_binary(&self) -> Vec<u8> {
+        let mut rlp_enc = RlpEncoder::default();
+        self.encode_payload(&mut rlp_enc);
+        if self.tx_type == LEGACY_TX_TYPE {
+            return rlp_enc.to_bytes();
+        }
+
+        let mut b = vec![self.tx_type];
+        rlp_enc.append_to_bytes(&mut b);
+        b
+    }
+
+    /// Decodes the consensus encoding of a receipt, see `encode_binary`.
+    pub fn decode_binary(b: &[u8]) -> Result<Self, DecodeError> {
+        let (&first, payload) = b.split_first().ok_or(DecodeError::InputTooShort(0))?;
+        if first > 0x7F {
+            return Self::decode_payload(LEGACY_TX_TYPE, b);
+        }
+        if first == LEGACY_TX_TYPE {
+            return Err(DecodeError::InvalidValue(0));
+        }
+        Self::decode_payload(first, payload).map_err(|e| e.shift(1))
+    }
+
+    fn decode_payload(tx_type: u8, payload: &[u8]) -> Result<Self, DecodeError> {
+        let (status, cumulative_gas_used, bloom, logs) = rlp::decode(payload)?;
+        Ok(Self {
+            tx_type,
+            status,
+            cumulative_gas_used,
+            bloom,
+            logs,
+        })
+    }
+}
+
+impl Encodable for Receipt {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        if self.tx_type == LEGACY_TX_TYPE {
+            return self.encode_payload(rlp_enc);
+        }
+        // The typed encoding is written in place, as a string holding the
+        // type byte followed by the payload.
+        rlp_enc.write_string_header(1 + self.payload_len());
+        rlp_enc.write_raw(&[self.tx_type]);
+        self.encode_payload(rlp_enc);
+    }
+
+    fn encoded_len(&self) -> usize {
+        if self.tx_type == LEGACY_TX_TYPE {
+            return self.payload_len();
+        }
+        string_len(1 + self.payload_len())
+    }
+}
+
+impl Decodable<'_> for Receipt {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let (kind, content, rest) = split(buf)?;
+        let raw = match kind {
+            Kind::List => &buf[..buf.len() - rest.len()],
+            _ => content,
+        };
+        let header = raw.as_ptr() as usize - buf.as_ptr() as usize;
+        let receipt = Self::decode_binary(raw).map_err(|e| e.shift(header))?;
+        if kind != Kind::List && receipt.tx_type == LEGACY_TX_TYPE {
+            // A legacy receipt must not be wrapped in a string.
+            return Err(DecodeError::InvalidValue(header));
+        }
+        *buf = rest;
+
+        Ok(receipt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        types::transaction::DYNAMIC_FEE_TX_TYPE,
+        utils::{from_hex, to_hex},
+    };
+
+    fn receipt(tx_type: u8, status: ReceiptStatus) -> Receipt {
+        Receipt::new(tx_type, status, 21000, Vec::new())
+    }
+
+    #[test]
+    fn encodings() {
+        let payload = format!("01825208b90100{}c0", "00".repeat(256));
+        let legacy = receipt(LEGACY_TX_TYPE, ReceiptStatus::Success);
+        assert_eq!(
+            to_hex(&legacy.encode_binary()),
+            format!("f90108{}", payload)
+        );
+        assert_eq!(rlp::encode(&legacy), legacy.encode_binary());
+
+        let typed = receipt(DYNAMIC_FEE_TX_TYPE, ReceiptStatus::Success);
+        assert_eq!(
+            to_hex(&typed.encode_binary()),
+            format!("02f90108{}", payload)
+        );
+        assert_eq!(
+            to_hex(&rlp::encode(&typed)),
+            format!("b9010c02f90108{}", payload)
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        let log = Log {
+            address: [0x11; 20],
+            topics: vec![[0x22; 32], [0x33; 32]],
+            data: vec![0x01, 0x02],
+        };
+        let receipts = [
+            receipt(LEGACY_TX_TYPE, ReceiptStatus::Failed),
+            receipt(LEGACY_TX_TYPE, ReceiptStatus::PostState([0xAB; 32])),
+            Receipt::new(0x01, ReceiptStatus::Success, 1, vec![log.clone()]),
+            Receipt::new(0x03, ReceiptStatus::Failed, 2, vec![log.clone(), log]),
+        ];
+        for receipt in &receipts {
+            let binary = receipt.encode_binary();
+            assert_eq!(&Receipt::decode_binary(&binary).unwrap(), receipt);
+            let enc = rlp::encode(receipt);
+            assert_eq!(&rlp::decode::<Receipt>(&enc).unwrap(), receipt);
+            assert_eq!(receipt.encoded_len(), enc.len());
+            let mut out = Vec::new();
+            rlp::encode_to(receipt, &mut out).unwrap();
+            assert_eq!(out, enc);
+            assert_eq!(
+                receipt.status.encoded_len(),
+                rlp::encode(&receipt.status).len()
+            );
+        }
+        let list = rlp::encode(&receipts.to_vec());
+        assert_eq!(rlp::decode::<Vec<Receipt>>(&list).unwrap(), receipts);
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert_eq!(
+            rlp::decode::<ReceiptStatus>(&[0x02]),
+            Err(DecodeError::InvalidValue(0))
+        );
+        assert_eq!(
+            Receipt::decode_binary(&[]),
+            Err(DecodeError::InputTooShort(0))
+        );
+        assert_eq!(
+            Receipt::decode_binary(&from_hex("00c0").unwrap()),
+            Err(DecodeError::InvalidValue(0))
+        );
+
+        // A legacy receipt must not be wrapped in a string.
+        let legacy = receipt(LEGACY_TX_TYPE, ReceiptStatus::Success);
+        let wrapped = rlp::encode(&legacy.encode_binary());
+        assert_eq!(
+            rlp::decode::<Receipt>(&wrapped),
+            Err(DecodeError::InvalidValue(3))
+        );
+    }
+}
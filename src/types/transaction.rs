@@ -0,0 +1,778 @@
+use crate::{
+    rlp::{
+        self,
+        decode::{split, Kind},
+        list_len,
+        rlp_encoder::RlpEncoder,
+        string_len, Decodable, DecodeError, Encodable, RlpDecodable, RlpEncodable,
+    },
+    trie::types::{Address, Hash},
+    utils::keccak256,
+};
+
+use super::u256::U256;
+
+// Transaction types.
+pub const LEGACY_TX_TYPE: u8 = 0x00;
+pub const ACCESS_LIST_TX_TYPE: u8 = 0x01;
+pub const DYNAMIC_FEE_TX_TYPE: u8 = 0x02;
+pub const BLOB_TX_TYPE: u8 = 0x03;
+pub const SET_CODE_TX_TYPE: u8 = 0x04;
+
+// The prefix of the message signed by the authority of an EIP-7702
+// authorization.
+const SET_CODE_AUTH_MAGIC: u8 = 0x05;
+
+/// AccessTuple is the element type of an EIP-2930 access list.
+#[derive(Debug, Clone, Default, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct AccessTuple {
+    pub address: Address,
+    pub storage_keys: Vec<Hash>,
+}
+
+/// AccessList is an EIP-2930 access list.
+pub type AccessList = Vec<AccessTuple>;
+
+/// Authorization is a signed EIP-7702 authorization, which sets the code of
+/// the signing account to a delegation to address.
+#[derive(Debug, Clone, Default, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct Authorization {
+    pub chain_id: U256,
+    pub address: Address,
+    pub nonce: u64,
+    pub y_parity: u8,
+    pub r: U256,
+    pub s: U256,
+}
+
+impl Authorization {
+    /// Returns the hash signed by the authority,
+    /// `keccak(0x05 || rlp([chain_id, address, nonce]))`.
+    pub fn signing_hash(&self) -> Hash {
+        let mut b = vec![SET_CODE_AUTH_MAGIC];
+        b.extend(rlp::encode(&(self.chain_id, self.address, self.nonce)));
+        keccak256(&b)
+    }
+}
+
+/// LegacyTx is the transaction data of the original Ethereum transactions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, RlpDecodable)]
+pub struct LegacyTx {
+    pub nonce: u64,
+    pub gas_price: U256,
+    pub gas: u64,
+    pub to: Option<Address>, // None means contract creation
+    pub value: U256,
+    pub data: Vec<u8>,
+    pub v: U256,
+    pub r: U256,
+    pub s: U256,
+}
+
+/// AccessListTx is the data of EIP-2930 access list transactions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, RlpDecodable)]
+pub struct AccessListTx {
+    pub chain_id: U256,
+    pub nonce: u64,
+    pub gas_price: U256,
+    pub gas: u64,
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Vec<u8>,
+    pub access_list: AccessList,
+    pub y_parity: u8,
+    pub r: U256,
+    pub s: U256,
+}
+
+/// DynamicFeeTx represents an EIP-1559 transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq, RlpDecodable)]
+pub struct DynamicFeeTx {
+    pub chain_id: U256,
+    pub nonce: u64,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas: u64,
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Vec<u8>,
+    pub access_list: AccessList,
+    pub y_parity: u8,
+    pub r: U256,
+    pub s: U256,
+}
+
+/// BlobTx represents an EIP-4844 transaction in its consensus form, without
+/// the blob sidecar. Blob transactions cannot create contracts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, RlpDecodable)]
+pub struct BlobTx {
+    pub chain_id: U256,
+    pub nonce: u64,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas: u64,
+    pub to: Address,
+    pub value: U256,
+    pub data: Vec<u8>,
+    pub access_list: AccessList,
+    pub max_fee_per_blob_gas: U256,
+    pub blob_versioned_hashes: Vec<Hash>,
+    pub y_parity: u8,
+    pub r: U256,
+    pub s: U256,
+}
+
+/// SetCodeTx implements the EIP-7702 transaction type which temporarily
+/// installs the code at the signer's address.
+#[derive(Debug, Clone, Default, PartialEq, Eq, RlpDecodable)]
+pub struct SetCodeTx {
+    pub chain_id: U256,
+    pub nonce: u64,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas: u64,
+    pub to: Address,
+    pub value: U256,
+    pub data: Vec<u8>,
+    pub access_list: AccessList,
+    pub authorization_list: Vec<Authorization>,
+    pub y_parity: u8,
+    pub r: U256,
+    pub s: U256,
+}
+
+impl LegacyTx {
+    fn encode_fields(&self, rlp_enc: &mut RlpEncoder) {
+        self.nonce.encode(rlp_enc);
+        self.gas_price.encode(rlp_enc);
+        self.gas.encode(rlp_enc);
+        self.to.encode(rlp_enc);
+        self.value.encode(rlp_enc);
+        self.data.encode(rlp_enc);
+    }
+
+    /// Returns the chain id the transaction is replay protected for, derived
+    /// from v as specified by EIP-155. Returns `None` for unprotected
+    /// transactions.
+    pub fn chain_id(&self) -> Option<U256> {
+        if matches!(u64::try_from(self.v), Ok(0 | 1 | 27 | 28)) {
+            return None;
+        }
+        self.v
+            .checked_sub(U256::from(35u64))
+            .map(|v| v / U256::from(2u64))
+    }
+
+    /// Returns the hash signed by the sender. Protected transactions sign
+    /// `[nonce, gas_price, gas, to, value, data, chain_id, 0, 0]`,
+    /// unprotected ones only the first six fields.
+    pub fn signing_hash(&self) -> Hash {
+        let mut rlp_enc = RlpEncoder::default();
+        let offset = rlp_enc.list();
+        self.encode_fields(&mut rlp_enc);
+        if let Some(chain_id) = self.chain_id() {
+            chain_id.encode(&mut rlp_enc);
+            0u64.encode(&mut rlp_enc);
+            0u64.encode(&mut rlp_enc);
+        }
+        rlp_enc.list_end(offset);
+
+        keccak256(&rlp_enc.to_bytes())
+    }
+}
+
+impl Encodable for LegacyTx {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        let offset = rlp_enc.list();
+        self.encode_fields(rlp_enc);
+        self.v.encode(rlp_enc);
+        self.r.encode(rlp_enc);
+        self.s.encode(rlp_enc);
+        rlp_enc.list_end(offset);
+    }
+
+    fn encoded_len(&self) -> usize {
+        list_len(
+            self.nonce.encoded_len()
+                + self.gas_price.encoded_len()
+                + self.gas.encoded_len()
+                + self.to.encoded_len()
+                + self.value.encoded_len()
+                + self.data.encoded_len()
+                + self.v.encoded_len()
+                + self.r.encoded_len()
+                + self.s.encoded_len(),
+        )
+    }
+}
+
+/// Implements the encoding and the signing hash of a typed transaction.
+/// encode_fields writes the fields covered by the signature, fields_len
+/// returns their encoded length.
+macro_rules! impl_typed_tx {
+    ($ty:ident, $tx_type:expr) => {
+        impl $ty {
+            /// Returns the hash signed by the sender,
+            /// `keccak(type || rlp([fields without signature]))`.
+            pub fn signing_hash(&self) -> Hash {
+                let mut rlp_enc = RlpEncoder::default();
+                let offset = rlp_enc.list();
+                self.encode_fields(&mut rlp_enc);
+                rlp_enc.list_end(offset);
+
+                let mut b = vec![$tx_type];
+                rlp_enc.append_to_bytes(&mut b);
+                keccak256(&b)
+            }
+        }
+
+        impl Encodable for $ty {
+            fn encode(&self, rlp_enc: &mut RlpEncoder) {
+                let offset = rlp_enc.list();
+                self.encode_fields(rlp_enc);
+                self.y_parity.encode(rlp_enc);
+                self.r.encode(rlp_enc);
+                self.s.encode(rlp_enc);
+                rlp_enc.list_end(offset);
+            }
+
+            fn encoded_len(&self) -> usize {
+                list_len(
+                    self.fields_len()
+                        + self.y_parity.encoded_len()
+                        + self.r.encoded_len()
+                        + self.s.encoded_len(),
+                )
+            }
+        }
+    };
+}
+
+impl AccessListTx {
+    fn encode_fields(&self, rlp_enc: &mut RlpEncoder) {
+        self.chain_id.encode(rlp_enc);
+        self.nonce.encode(rlp_enc);
+        self.gas_price.encode(rlp_enc);
+        self.gas.encode(rlp_enc);
+        self.to.encode(rlp_enc);
+        self.value.encode(rlp_enc);
+        self.data.encode(rlp_enc);
+        self.access_list.encode(rlp_enc);
+    }
+
+    fn fields_len(&self) -> usize {
+        self.chain_id.encoded_len()
+            + self.nonce.encoded_len()
+            + self.gas_price.encoded_len()
+            + self.gas.encoded_len()
+            + self.to.encoded_len()
+            + self.value.encoded_len()
+            + self.data.encoded_len()
+            + self.access_list.encoded_len()
+    }
+}
+
+impl DynamicFeeTx {
+    fn encode_fields(&self, rlp_enc: &mut RlpEncoder) {
+        self.chain_id.encode(rlp_enc);
+        self.nonce.encode(rlp_enc);
+        self.max_priority_fee_per_gas.encode(rlp_enc);
+        self.max_fee_per_gas.encode(rlp_enc);
+        self.gas.encode(rlp_enc);
+        self.to.encode(rlp_enc);
+        self.value.encode(rlp_enc);
+        self.data.encode(rlp_enc);
+        self.access_list.encode(rlp_enc);
+    }
+
+    fn fields_len(&self) -> usize {
+        self.chain_id.encoded_len()
+            + self.nonce.encoded_len()
+            + self.max_priority_fee_per_gas.encoded_len()
+            + self.max_fee_per_gas.encoded_len()
+            + self.gas.encoded_len()
+            + self.to.encoded_len()
+            + self.value.encoded_len()
+            + self.data.encoded_len()
+            + self.access_list.encoded_len()
+    }
+}
+
+impl BlobTx {
+    fn encode_fields(&self, rlp_enc: &mut RlpEncoder) {
+        self.chain_id.encode(rlp_enc);
+        self.nonce.encode(rlp_enc);
+        self.max_priority_fee_per_gas.encode(rlp_enc);
+        self.max_fee_per_gas.encode(rlp_enc);
+        self.gas.encode(rlp_enc);
+        self.to.encode(rlp_enc);
+        self.value.encode(rlp_enc);
+        self.data.encode(rlp_enc);
+        self.access_list.encode(rlp_enc);
+        self.max_fee_per_blob_gas.encode(rlp_enc);
+        self.blob_versioned_hashes.encode(rlp_enc);
+    }
+
+    fn fields_len(&self) -> usize {
+        self.chain_id.encoded_len()
+            + self.nonce.encoded_len()
+            + self.max_priority_fee_per_gas.encoded_len()
+            + self.max_fee_per_gas.encoded_len()
+            + self.gas.encoded_len()
+            + self.to.encoded_len()
+            + self.value.encoded_len()
+            + self.data.encoded_len()
+            + self.access_list.encoded_len()
+            + self.max_fee_per_blob_gas.encoded_len()
+            + self.blob_versioned_hashes.encoded_len()
+    }
+}
+
+impl SetCodeTx {
+    fn encode_fields(&self, rlp_enc: &mut RlpEncoder) {
+        self.chain_id.encode(rlp_enc);
+        self.nonce.encode(rlp_enc);
+        self.max_priority_fee_per_gas.encode(rlp_enc);
+        self.max_fee_per_gas.encode(rlp_enc);
+        self.gas.encode(rlp_enc);
+        self.to.encode(rlp_enc);
+        self.value.encode(rlp_enc);
+        self.data.encode(rlp_enc);
+        self.access_list.encode(rlp_enc);
+        self.authorization_list.encode(rlp_enc);
+    }
+
+    fn fields_len(&self) -> usize {
+        self.chain_id.encoded_len()
+            + self.nonce.encoded_len()
+            + self.max_priority_fee_per_gas.encoded_len()
+            + self.max_fee_per_gas.encoded_len()
+            + self.gas.encoded_len()
+            + self.to.encoded_len()
+            + self.value.encoded_len()
+            + self.data.encoded_len()
+            + self.access_list.encoded_len()
+            + self.authorization_list.encoded_len()
+    }
+}
+
+impl_typed_tx!(AccessListTx, ACCESS_LIST_TX_TYPE);
+impl_typed_tx!(DynamicFeeTx, DYNAMIC_FEE_TX_TYPE);
+impl_typed_tx!(BlobTx, BLOB_TX_TYPE);
+impl_typed_tx!(SetCodeTx, SET_CODE_TX_TYPE);
+
+/// Transaction is an Ethereum transaction of any type.
+///
+/// The RLP encoding is the one used in block bodies: legacy transactions are
+/// encoded as a list, typed transactions as a byte string holding their
+/// EIP-2718 envelope `type || rlp(payload)`. The envelope itself is returned
+/// by `encode_binary`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transaction {
+    Legacy(LegacyTx),
+    AccessList(AccessListTx),
+    DynamicFee(DynamicFeeTx),
+    Blob(BlobTx),
+    SetCode(SetCodeTx),
+}
+
+impl Transaction {
+    pub fn tx_type(&self) -> u8 {
+        match self {
+            Transaction::Legacy(_) => LEGACY_TX_TYPE,
+            Transaction::AccessList(_) => ACCESS_LIST_TX_TYPE,
+            Transaction::DynamicFee(_) => DYNAMIC_FEE_TX_TYPE,
+            Transaction::Blob(_) => BLOB_TX_TYPE,
+            Transaction::SetCode(_) => SET_CODE_TX_TYPE,
+        }
+    }
+
+    /// Returns the canonical encoding of the transaction: the RLP list for
+    /// legacy transactions, the EIP-2718 envelope for typed transactions.
+    /// This is the encoding which is hashed and stored in the transaction
+    /// trie.
+    pub fn encode_binary(&self) -> Vec<u8> {
+        match self {
+            Transaction::Legacy(tx) => rlp::encode(tx),
+            Transaction::AccessList(tx) => envelope(ACCESS_LIST_TX_TYPE, tx),
+            Transaction::DynamicFee(tx) => envelope(DYNAMIC_FEE_TX_TYPE, tx),
+            Transaction::Blob(tx) => envelope(BLOB_TX_TYPE, tx),
+            Transaction::SetCode(tx) => envelope(SET_CODE_TX_TYPE, tx),
+        }
+    }
+
+    /// Decodes the canonical encoding of a transaction, see `encode_binary`.
+    pub fn decode_binary(b: &[u8]) -> Result<Self, DecodeError> {
+        let (&tx_type, payload) = b.split_first().ok_or(DecodeError::InputTooShort(0))?;
+        if tx_type > 0x7F {
+            return rlp::decode(b).map(Transaction::Legacy);
+        }
+        let shift = |e: DecodeError| e.shift(1);
+        match tx_type {
+            ACCESS_LIST_TX_TYPE => rlp::decode(payload)
+                .map(Transaction::AccessList)
+                .map_err(shift),
+            DYNAMIC_FEE_TX_TYPE => rlp::decode(payload)
+                .map(Transaction::DynamicFee)
+                .map_err(shift),
+            BLOB_TX_TYPE => rlp::decode(payload).map(Transaction::Blob).map_err(shift),
+            SET_CODE_TX_TYPE => rlp::decode(payload)
+                .map(Transaction::SetCode)
+                .map_err(shift),
+            _ => Err(DecodeError::InvalidValue(0)),
+        }
+    }
+
+    /// Returns the transaction hash, the Keccak-256 hash of `encode_binary`.
+    pub fn hash(&self) -> Hash {
+        keccak256(&self.encode_binary())
+    }
+
+    /// Returns the hash signed by the sender.
+    pub fn signing_hash(&self) -> Hash {
+        match self {
+            Transaction::Legacy(tx) => tx.signing_hash(),
+            Transaction::AccessList(tx) => tx.signing_hash(),
+            Transaction::DynamicFee(tx) => tx.signing_hash(),
+            Transaction::Blob(tx) => tx.signing_hash(),
+            Transaction::SetCode(tx) => tx.signing_hash(),
+        }
+    }
+
+    /// Returns the chain id of the transaction, `None` for legacy
+    /// transactions without replay protection.
+    pub fn chain_id(&self) -> Option<U256> {
+        match self {
+            Transaction::Legacy(tx) => tx.chain_id(),
+            Transaction::AccessList(tx) => Some(tx.chain_id),
+            Transaction::DynamicFee(tx) => Some(tx.chain_id),
+            Transaction::Blob(tx) => Some(tx.chain_id),
+            Transaction::SetCode(tx) => Some(tx.chain_id),
+        }
+    }
+
+    pub fn nonce(&self) -> u64 {
+        match self {
+            Transaction::Legacy(tx) => tx.nonce,
+            Transaction::AccessList(tx) => tx.nonce,
+            Transaction::DynamicFee(tx) => tx.nonce,
+            Transaction::Blob(tx) => tx.nonce,
+            Transaction::SetCode(tx) => tx.nonce,
+        }
+    }
+
+    pub fn gas(&self) -> u64 {
+        match self {
+            Transaction::Legacy(tx) => tx.gas,
+            Transaction::AccessList(tx) => tx.gas,
+            Transaction::DynamicFee(tx) => tx.gas,
+            Transaction::Blob(tx) => tx.gas,
+            Transaction::SetCode(tx) => tx.gas,
+        }
+    }
+
+    /// Returns the recipient, `None` for contract creations.
+    pub fn to(&self) -> Option<Address> {
+        match self {
+            Transaction::Legacy(tx) => tx.to,
+            Transaction::AccessList(tx) => tx.to,
+            Transaction::DynamicFee(tx) => tx.to,
+            Transaction::Blob(tx) => Some(tx.to),
+            Transaction::SetCode(tx) => Some(tx.to),
+        }
+    }
+
+    pub fn value(&self) -> U256 {
+        match self {
+            Transaction::Legacy(tx) => tx.value,
+            Transaction::AccessList(tx) => tx.value,
+            Transaction::DynamicFee(tx) => tx.value,
+            Transaction::Blob(tx) => tx.value,
+            Transaction::SetCode(tx) => tx.value,
+        }
+    }
+
+    pub fn data(&self) -> &[u8] {
+        match self {
+            Transaction::Legacy(tx) => &tx.data,
+            Transaction::AccessList(tx) => &tx.data,
+            Transaction::DynamicFee(tx) => &tx.data,
+            Transaction::Blob(tx) => &tx.data,
+            Transaction::SetCode(tx) => &tx.data,
+        }
+    }
+
+    /// Returns the access list, empty for legacy transactions.
+    pub fn access_list(&self) -> &[AccessTuple] {
+        match self {
+            Transaction::Legacy(_) => &[],
+            Transaction::AccessList(tx) => &tx.access_list,
+            Transaction::DynamicFee(tx) => &tx.access_list,
+            Transaction::Blob(tx) => &tx.access_list,
+            Transaction::SetCode(tx) => &tx.access_list,
+        }
+    }
+}
+
+/// Returns the EIP-2718 envelope of a typed transaction.
+fn envelope(tx_type: u8, payload: &impl Encodable) -> Vec<u8> {
+    let mut rlp_enc = RlpEncoder::default();
+    payload.encode(&mut rlp_enc);
+
+    let mut b = vec![tx_type];
+    rlp_enc.append_to_bytes(&mut b);
+    b
+}
+
+impl Encodable for Transaction {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        let tx_type = self.tx_type();
+        let payload: &dyn Encodable = match self {
+            Transaction::Legacy(tx) => return tx.encode(rlp_enc),
+            Transaction::AccessList(tx) => tx,
+            Transaction::DynamicFee(tx) => tx,
+            Transaction::Blob(tx) => tx,
+            Transaction::SetCode(tx) => tx,
+        };
+        // The envelope is written in place, as a string holding the type
+        // byte followed by the payload.
+        rlp_enc.write_string_header(1 + payload.encoded_len());
+        rlp_enc.write_raw(&[tx_type]);
+        payload.encode(rlp_enc);
+    }
+
+    fn encoded_len(&self) -> usize {
+        match self {
+            Transaction::Legacy(tx) => tx.encoded_len(),
+            Transaction::AccessList(tx) => string_len(1 + tx.encoded_len()),
+            Transaction::DynamicFee(tx) => string_len(1 + tx.encoded_len()),
+            Transaction::Blob(tx) => string_len(1 + tx.encoded_len()),
+            Transaction::SetCode(tx) => string_len(1 + tx.encoded_len()),
+        }
+    }
+}
+
+impl Decodable<'_> for Transaction {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let (kind, content, rest) = split(buf)?;
+        if kind == Kind::List {
+            return LegacyTx::decode(buf).map(Transaction::Legacy);
+        }
+        let header = content.as_ptr() as usize - buf.as_ptr() as usize;
+        let tx = match Self::decode_binary(content).map_err(|e| e.shift(header))? {
+            // A legacy transaction must not be wrapped in a string.
+            Transaction::Legacy(_) => return Err(DecodeError::InvalidValue(header)),
+            tx => tx,
+        };
+        *buf = rest;
+
+        Ok(tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{from_hex, to_hex};
+
+    fn address(hex: &str) -> Address {
+        from_hex(hex).unwrap().try_into().unwrap()
+    }
+
+    /// The example transaction of EIP-155.
+    fn eip155_tx() -> LegacyTx {
+        LegacyTx {
+            nonce: 9,
+            gas_price: U256::from(20_000_000_000u64),
+            gas: 21000,
+            to: Some(address("3535353535353535353535353535353535353535")),
+            value: U256::from(1_000_000_000_000_000_000u64),
+            data: Vec::new(),
+            v: U256::from(37u64),
+            r: U256::from_dec_str(
+                "18515461264373351373200002665853028612451056578545711640558177340181847433846",
+            )
+            .unwrap(),
+            s: U256::from_dec_str(
+                "46948507304638947509940763649030358759909902576025900602547168820602576006531",
+            )
+            .unwrap(),
+        }
+    }
+
+    /// The access list transaction of geth's EIP-2718 tests.
+    fn access_list_tx() -> AccessListTx {
+        AccessListTx {
+            chain_id: U256::ONE,
+            nonce: 3,
+            gas_price: U256::ONE,
+            gas: 25000,
+            to: Some(address("b94f5374fce5edbc8e2a8697c15331677e6ebf0b")),
+            value: U256::from(10u64),
+            data: vec![0x55, 0x44],
+            access_list: Vec::new(),
+            y_parity: 1,
+            r: U256::from_hex_str(
+                "c9519f4f2b30335884581971573fadf60c6204f59a911df35ee8a540456b2660",
+            )
+            .unwrap(),
+            s: U256::from_hex_str(
+                "32f1e8e2c5dd761f9e4f88f41c8310aeaba26a8bfcdacfedfa12ec3862d37521",
+            )
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn eip155_signing_hash() {
+        let tx = eip155_tx();
+        assert_eq!(tx.chain_id(), Some(U256::ONE));
+        assert_eq!(
+            to_hex(&tx.signing_hash()),
+            "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+        );
+
+        let raw = "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+        let tx = Transaction::Legacy(tx);
+        assert_eq!(to_hex(&tx.encode_binary()), raw);
+        assert_eq!(to_hex(&rlp::encode(&tx)), raw);
+        assert_eq!(tx.encoded_len(), raw.len() / 2);
+        assert_eq!(
+            Transaction::decode_binary(&from_hex(raw).unwrap()).unwrap(),
+            tx
+        );
+        assert_eq!(tx.hash(), keccak256(&from_hex(raw).unwrap()));
+    }
+
+    #[test]
+    fn unprotected_legacy() {
+        let tx = LegacyTx {
+            v: U256::from(27u64),
+            ..eip155_tx()
+        };
+        assert_eq!(tx.chain_id(), None);
+        // Only the first six fields are signed.
+        let mut fields = RlpEncoder::default();
+        let offset = fields.list();
+        tx.encode_fields(&mut fields);
+        fields.list_end(offset);
+        assert_eq!(tx.signing_hash(), keccak256(&fields.to_bytes()));
+    }
+
+    #[test]
+    fn access_list_tx_encoding() {
+        let tx = access_list_tx();
+        assert_eq!(
+            to_hex(&tx.signing_hash()),
+            "49b486f0ec0a60dfbbca2d30cb07c9e8ffb2a2ff41f29a1ab6737475f6ff69f3"
+        );
+
+        let envelope = "01f8630103018261a894b94f5374fce5edbc8e2a8697c15331677e6ebf0b0a825544c001a0c9519f4f2b30335884581971573fadf60c6204f59a911df35ee8a540456b2660a032f1e8e2c5dd761f9e4f88f41c8310aeaba26a8bfcdacfedfa12ec3862d37521";
+        let tx = Transaction::AccessList(tx);
+        assert_eq!(to_hex(&tx.encode_binary()), envelope);
+        // In block bodies the envelope is wrapped in a string.
+        let enc = rlp::encode(&tx);
+        assert_eq!(to_hex(&enc), format!("b866{}", envelope));
+        assert_eq!(tx.encoded_len(), enc.len());
+        assert_eq!(rlp::decode::<Transaction>(&enc).unwrap(), tx);
+        assert_eq!(
+            Transaction::decode_binary(&from_hex(envelope).unwrap()).unwrap(),
+            tx
+        );
+    }
+
+    #[test]
+    fn typed_round_trips() {
+        let to = address("b94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+        let access_list = vec![AccessTuple {
+            address: to,
+            storage_keys: vec![[0x01; 32], [0x02; 32]],
+        }];
+        let txs = [
+            Transaction::DynamicFee(DynamicFeeTx {
+                chain_id: U256::ONE,
+                nonce: 1,
+                max_priority_fee_per_gas: U256::from(2u64),
+                max_fee_per_gas: U256::from(3u64),
+                gas: 21000,
+                to: None,
+                data: vec![0x60, 0x00],
+                access_list: access_list.clone(),
+                r: U256::ONE,
+                s: U256::ONE,
+                ..Default::default()
+            }),
+            Transaction::Blob(BlobTx {
+                chain_id: U256::ONE,
+                to,
+                max_fee_per_blob_gas: U256::from(7u64),
+                blob_versioned_hashes: vec![[0x01; 32]],
+                y_parity: 1,
+                ..Default::default()
+            }),
+            Transaction::SetCode(SetCodeTx {
+                chain_id: U256::ONE,
+                to,
+                access_list,
+                authorization_list: vec![Authorization {
+                    chain_id: U256::ONE,
+                    address: to,
+                    nonce: 5,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+        ];
+        for tx in txs {
+            let envelope = tx.encode_binary();
+            assert_eq!(envelope[0], tx.tx_type());
+            assert_eq!(Transaction::decode_binary(&envelope).unwrap(), tx);
+            // The envelope written in place matches the wrapped one.
+            let enc = rlp::encode(&tx);
+            assert_eq!(enc, rlp::encode(&envelope));
+            assert_eq!(tx.encoded_len(), enc.len());
+            let mut out = Vec::new();
+            rlp::encode_to(&tx, &mut out).unwrap();
+            assert_eq!(out, enc);
+            assert_eq!(rlp::decode::<Transaction>(&enc).unwrap(), tx);
+            assert_eq!(tx.hash(), keccak256(&envelope));
+            assert_eq!(tx.chain_id(), Some(U256::ONE));
+        }
+    }
+
+    #[test]
+    fn authorization_signing_hash() {
+        let auth = Authorization {
+            chain_id: U256::ONE,
+            address: address("b94f5374fce5edbc8e2a8697c15331677e6ebf0b"),
+            nonce: 5,
+            ..Default::default()
+        };
+        let msg = "05d70194b94f5374fce5edbc8e2a8697c15331677e6ebf0b05";
+        assert_eq!(auth.signing_hash(), keccak256(&from_hex(msg).unwrap()));
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert_eq!(
+            Transaction::decode_binary(&[]),
+            Err(DecodeError::InputTooShort(0))
+        );
+        assert_eq!(
+            Transaction::decode_binary(&[0x05, 0xC0]),
+            Err(DecodeError::InvalidValue(0))
+        );
+        assert_eq!(
+            Transaction::decode_binary(&[0x02, 0xC0]),
+            Err(DecodeError::InputTooShort(2))
+        );
+
+        // A legacy transaction must not be wrapped in a string.
+        let legacy = rlp::encode(&eip155_tx());
+        let wrapped = rlp::encode(&legacy);
+        assert_eq!(
+            rlp::decode::<Transaction>(&wrapped),
+            Err(DecodeError::InvalidValue(2))
+        );
+    }
+}
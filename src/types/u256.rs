@@ -0,0 +1,585 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, AddAssign, Div, Mul, Rem, Sub, SubAssign},
+    str::FromStr,
+};
+
+use crate::{
+    rlp::{
+        bytes_len, decode::split_string, rlp_encoder::RlpEncoder, Decodable, DecodeError, Encodable,
+    },
+    utils::trim_left_zeroes,
+};
+
+/// U256 is a 256-bit unsigned integer, as used for balances and difficulties.
+/// The limbs are stored least significant first.
+///
+/// Like the primitive integers, the operators panic on overflow and division
+/// by zero. The checked variants return `None` instead.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct U256(pub [u64; 4]);
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+    pub const ONE: U256 = U256([1, 0, 0, 0]);
+    pub const MAX: U256 = U256([u64::MAX; 4]);
+
+    pub fn is_zero(&self) -> bool {
+        *self == Self::ZERO
+    }
+
+    /// Returns the number of bits needed to represent the value.
+    pub fn bits(&self) -> u32 {
+        for i in (0..4).rev() {
+            if self.0[i] != 0 {
+                return i as u32 * 64 + 64 - self.0[i].leading_zeros();
+            }
+        }
+        0
+    }
+
+    /// Returns the lowest 64 bits of the value.
+    pub fn low_u64(&self) -> u64 {
+        self.0[0]
+    }
+
+    pub fn from_be_bytes(b: [u8; 32]) -> Self {
+        let mut limbs = [0; 4];
+        for (i, chunk) in b.chunks_exact(8).enumerate() {
+            limbs[3 - i] = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+        U256(limbs)
+    }
+
+    /// Interprets b as a big endian integer of at most 32 bytes.
+    pub fn from_be_slice(b: &[u8]) -> Result<Self, U256Error> {
+        if b.len() > 32 {
+            return Err(U256Error::Overflow);
+        }
+        let mut buf = [0; 32];
+        buf[32 - b.len()..].copy_from_slice(b);
+        Ok(Self::from_be_bytes(buf))
+    }
+
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut b = [0; 32];
+        for i in 0..4 {
+            b[i * 8..i * 8 + 8].copy_from_slice(&self.0[3 - i].to_be_bytes());
+        }
+        b
+    }
+
+    pub fn overflowing_add(self, other: Self) -> (Self, bool) {
+        let mut r = [0; 4];
+        let mut carry = false;
+        for (i, limb) in r.iter_mut().enumerate() {
+            let (s, c1) = self.0[i].overflowing_add(other.0[i]);
+            let (s, c2) = s.overflowing_add(carry as u64);
+            *limb = s;
+            carry = c1 || c2;
+        }
+        (U256(r), carry)
+    }
+
+    pub fn overflowing_sub(self, other: Self) -> (Self, bool) {
+        let mut r = [0; 4];
+        let mut borrow = false;
+        for (i, limb) in r.iter_mut().enumerate() {
+            let (d, b1) = self.0[i].overflowing_sub(other.0[i]);
+            let (d, b2) = d.overflowing_sub(borrow as u64);
+            *limb = d;
+            borrow = b1 || b2;
+        }
+        (U256(r), borrow)
+    }
+
+    pub fn overflowing_mul(self, other: Self) -> (Self, bool) {
+        // Schoolbook multiplication into 512 bits, the value overflows if
+        // any of the upper limbs is set.
+        let mut r = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let t = self.0[i] as u128 * other.0[j] as u128 + r[i + j] as u128 + carry;
+                r[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            r[i + 4] = carry as u64;
+        }
+        let overflow = r[4..].iter().any(|&l| l != 0);
+        (U256([r[0], r[1], r[2], r[3]]), overflow)
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        match self.overflowing_add(other) {
+            (r, false) => Some(r),
+            _ => None,
+        }
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        match self.overflowing_sub(other) {
+            (r, false) => Some(r),
+            _ => None,
+        }
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        match self.overflowing_mul(other) {
+            (r, false) => Some(r),
+            _ => None,
+        }
+    }
+
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        self.div_rem(other).map(|(q, _)| q)
+    }
+
+    pub fn checked_rem(self, other: Self) -> Option<Self> {
+        self.div_rem(other).map(|(_, r)| r)
+    }
+
+    pub fn saturating_add(self, other: Self) -> Self {
+        self.checked_add(other).unwrap_or(Self::MAX)
+    }
+
+    pub fn saturating_sub(self, other: Self) -> Self {
+        self.checked_sub(other).unwrap_or(Self::ZERO)
+    }
+
+    /// Returns the quotient and remainder of self / other, or `None` if other
+    /// is zero.
+    pub fn div_rem(self, other: Self) -> Option<(Self, Self)> {
+        if other.is_zero() {
+            return None;
+        }
+        if self < other {
+            return Some((Self::ZERO, self));
+        }
+        if other.0[1..] == [0; 3] {
+            let (q, r) = self.div_rem_u64(other.0[0]);
+            return Some((q, U256::from(r)));
+        }
+
+        // Binary long division over the significant bits of self. The
+        // remainder is below other, but shifting it can carry out of the top
+        // limb if other is above 2^255, in which case it certainly exceeds
+        // other and the wrapping subtraction is exact.
+        let mut q = Self::ZERO;
+        let mut r = Self::ZERO;
+        for i in (0..self.bits()).rev() {
+            let carry = r.0[3] >> 63 == 1;
+            r = r.shl1();
+            r.0[0] |= self.bit(i) as u64;
+            if carry || r >= other {
+                r = r.overflowing_sub(other).0;
+                q.0[i as usize / 64] |= 1 << (i % 64);
+            }
+        }
+        Some((q, r))
+    }
+
+    fn div_rem_u64(self, d: u64) -> (Self, u64) {
+        let mut q = [0; 4];
+        let mut r = 0u128;
+        for i in (0..4).rev() {
+            let n = r << 64 | self.0[i] as u128;
+            q[i] = (n / d as u128) as u64;
+            r = n % d as u128;
+        }
+        (U256(q), r as u64)
+    }
+
+    fn bit(&self, i: u32) -> bool {
+        self.0[i as usize / 64] >> (i % 64) & 1 == 1
+    }
+
+    fn shl1(self) -> Self {
+        let l = self.0;
+        U256([
+            l[0] << 1,
+            l[1] << 1 | l[0] >> 63,
+            l[2] << 1 | l[1] >> 63,
+            l[3] << 1 | l[2] >> 63,
+        ])
+    }
+
+    /// Parses a decimal string.
+    pub fn from_dec_str(s: &str) -> Result<Self, U256Error> {
+        if s.is_empty() {
+            return Err(U256Error::Empty);
+        }
+        let ten = U256::from(10u64);
+        s.chars().try_fold(Self::ZERO, |acc, c| {
+            let d = c.to_digit(10).ok_or(U256Error::InvalidDigit)?;
+            acc.checked_mul(ten)
+                .and_then(|acc| acc.checked_add(U256::from(d as u64)))
+                .ok_or(U256Error::Overflow)
+        })
+    }
+
+    /// Parses a hex string with an optional `0x` prefix. Leading zeroes are
+    /// allowed.
+    pub fn from_hex_str(s: &str) -> Result<Self, U256Error> {
+        let s = s.strip_prefix("0x").unwrap_or(s);
+        if s.is_empty() {
+            return Err(U256Error::Empty);
+        }
+        let s = s.trim_start_matches('0');
+        if s.len() > 64 {
+            return Err(U256Error::Overflow);
+        }
+        s.chars().try_fold(Self::ZERO, |acc, c| {
+            let d = c.to_digit(16).ok_or(U256Error::InvalidDigit)?;
+            let mut acc = acc.shl1().shl1().shl1().shl1();
+            acc.0[0] |= d as u64;
+            Ok(acc)
+        })
+    }
+}
+
+impl From<u64> for U256 {
+    fn from(i: u64) -> Self {
+        U256([i, 0, 0, 0])
+    }
+}
+
+impl From<u128> for U256 {
+    fn from(i: u128) -> Self {
+        U256([i as u64, (i >> 64) as u64, 0, 0])
+    }
+}
+
+impl TryFrom<U256> for u64 {
+    type Error = U256Error;
+
+    fn try_from(i: U256) -> Result<Self, U256Error> {
+        if i.0[1..] != [0; 3] {
+            return Err(U256Error::Overflow);
+        }
+        Ok(i.0[0])
+    }
+}
+
+impl TryFrom<U256> for u128 {
+    type Error = U256Error;
+
+    fn try_from(i: U256) -> Result<Self, U256Error> {
+        if i.0[2..] != [0; 2] {
+            return Err(U256Error::Overflow);
+        }
+        Ok((i.0[1] as u128) << 64 | i.0[0] as u128)
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for U256 {
+    type Output = U256;
+
+    fn add(self, other: Self) -> Self {
+        self.checked_add(other)
+            .expect("attempt to add with overflow")
+    }
+}
+
+impl AddAssign for U256 {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for U256 {
+    type Output = U256;
+
+    fn sub(self, other: Self) -> Self {
+        self.checked_sub(other)
+            .expect("attempt to subtract with overflow")
+    }
+}
+
+impl SubAssign for U256 {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Mul for U256 {
+    type Output = U256;
+
+    fn mul(self, other: Self) -> Self {
+        self.checked_mul(other)
+            .expect("attempt to multiply with overflow")
+    }
+}
+
+impl Div for U256 {
+    type Output = U256;
+
+    fn div(self, other: Self) -> Self {
+        self.checked_div(other).expect("attempt to divide by zero")
+    }
+}
+
+impl Rem for U256 {
+    type Output = U256;
+
+    fn rem(self, other: Self) -> Self {
+        self.checked_rem(other)
+            .expect("attempt to calculate the remainder with a divisor of zero")
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Split off 19 decimal digits at a time, the most that fit in a u64.
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        let mut chunks = Vec::new();
+        let mut n = *self;
+        loop {
+            let (q, r) = n.div_rem_u64(CHUNK);
+            chunks.push(r);
+            if q.is_zero() {
+                break;
+            }
+            n = q;
+        }
+
+        let mut s = chunks.pop().unwrap().to_string();
+        for chunk in chunks.iter().rev() {
+            s.push_str(&format!("{:019}", chunk));
+        }
+        f.pad_integral(true, "", &s)
+    }
+}
+
+impl fmt::Debug for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::LowerHex for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = String::new();
+        for limb in self.0.iter().rev() {
+            if s.is_empty() {
+                if *limb != 0 {
+                    s = format!("{:x}", limb);
+                }
+            } else {
+                s.push_str(&format!("{:016x}", limb));
+            }
+        }
+        if s.is_empty() {
+            s.push('0');
+        }
+        f.pad_integral(true, "0x", &s)
+    }
+}
+
+/// Parses a decimal string, or a hex string if it starts with `0x`.
+impl FromStr for U256 {
+    type Err = U256Error;
+
+    fn from_str(s: &str) -> Result<Self, U256Error> {
+        if s.starts_with("0x") {
+            Self::from_hex_str(s)
+        } else {
+            Self::from_dec_str(s)
+        }
+    }
+}
+
+/// U256Error is returned when a value can't be converted to or from a U256.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum U256Error {
+    /// The value does not fit into the target type.
+    Overflow,
+    /// A string contains a character which is not a digit of its base.
+    InvalidDigit,
+    /// A string has no digits.
+    Empty,
+}
+
+impl fmt::Display for U256Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            U256Error::Overflow => "number too large",
+            U256Error::InvalidDigit => "invalid digit",
+            U256Error::Empty => "empty string",
+        };
+        write!(f, "u256: {}", msg)
+    }
+}
+
+impl std::error::Error for U256Error {}
+
+impl Encodable for U256 {
+    fn encode(&self, rlp_enc: &mut RlpEncoder) {
+        rlp_enc.write_big_int(&self.to_be_bytes());
+    }
+
+    fn encoded_len(&self) -> usize {
+        bytes_len(trim_left_zeroes(&self.to_be_bytes()))
+    }
+}
+
+impl Decodable<'_> for U256 {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let (content, rest) = split_string(buf)?;
+        if content.len() > 32 {
+            return Err(DecodeError::ValueTooLarge(0));
+        }
+        if content.first() == Some(&0) {
+            return Err(DecodeError::NonCanonicalInteger(0));
+        }
+        *buf = rest;
+
+        Ok(Self::from_be_slice(content).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        rlp::{decode, encode},
+        utils::to_hex,
+    };
+
+    fn dec(s: &str) -> U256 {
+        U256::from_dec_str(s).unwrap()
+    }
+
+    #[test]
+    fn parse_and_format() {
+        let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        assert_eq!(dec(max), U256::MAX);
+        assert_eq!(U256::MAX.to_string(), max);
+        assert_eq!(format!("{:x}", U256::MAX), "f".repeat(64));
+        assert_eq!(U256::ZERO.to_string(), "0");
+        assert_eq!(format!("{:#x}", U256::ZERO), "0x0");
+
+        // The terminal total difficulty of mainnet.
+        let ttd = dec("58750000000000000000000");
+        assert_eq!(format!("{:#x}", ttd), "0xc70d808a128d7380000");
+        assert_eq!("0xc70d808a128d7380000".parse::<U256>().unwrap(), ttd);
+        assert_eq!(U256::from_hex_str("000c70d808a128d7380000").unwrap(), ttd);
+        assert_eq!(ttd.bits(), 76);
+
+        assert_eq!(
+            dec("340282366920938463463374607431768211456"),
+            U256([0, 0, 1, 0])
+        );
+        assert_eq!(
+            U256::from_dec_str(&format!("{}6", &max[..max.len() - 1])),
+            Err(U256Error::Overflow)
+        );
+        assert_eq!(U256::from_dec_str(""), Err(U256Error::Empty));
+        assert_eq!(U256::from_dec_str("12a"), Err(U256Error::InvalidDigit));
+        assert_eq!(U256::from_hex_str("0x"), Err(U256Error::Empty));
+        assert_eq!(
+            U256::from_hex_str(&format!("1{}", "0".repeat(64))),
+            Err(U256Error::Overflow)
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = dec("123456789012345678901234567890");
+        assert_eq!(
+            a * a,
+            dec("15241578753238836750495351562536198787501905199875019052100")
+        );
+        assert_eq!(U256::MAX.overflowing_add(U256::ONE), (U256::ZERO, true));
+        assert_eq!(U256::ZERO.overflowing_sub(U256::ONE), (U256::MAX, true));
+        assert_eq!(U256::MAX.checked_mul(U256::from(2u64)), None);
+        assert_eq!(U256::MAX.saturating_add(U256::ONE), U256::MAX);
+        assert_eq!(U256::ONE.saturating_sub(U256::MAX), U256::ZERO);
+        assert_eq!(U256([0, 1, 0, 0]) - U256::ONE, U256::from(u64::MAX));
+        assert_eq!(
+            U256::MAX / U256::from(3u64),
+            U256([0x5555_5555_5555_5555; 4])
+        );
+        assert_eq!(U256::MAX % U256::from(10u64), U256::from(5u64));
+        assert_eq!(U256::ONE.checked_div(U256::ZERO), None);
+
+        // Division by a divisor of several limbs.
+        let n =
+            dec("82708635169511568159693560720491362752335703332600402885326845719937949755155");
+        let d = U256::from_hex_str("0x400000000000000200000000000000000000000000000011").unwrap();
+        assert_eq!(
+            n.div_rem(d),
+            Some((
+                dec("52704983067741576022"),
+                dec("1345093229011431608389632850366152783960287121283121501789")
+            ))
+        );
+
+        // Shifting the remainder carries out of the top limb.
+        let top = U256([0, 0, 0, 1 << 63]);
+        assert_eq!(
+            (top + U256::from(3u64)).div_rem(top + U256::ONE),
+            Some((U256::ONE, U256::from(2u64)))
+        );
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(U256::from(u128::MAX), U256([u64::MAX, u64::MAX, 0, 0]));
+        assert_eq!(u128::try_from(U256::from(u128::MAX)), Ok(u128::MAX));
+        assert_eq!(u64::try_from(U256([0, 1, 0, 0])), Err(U256Error::Overflow));
+        assert_eq!(U256([0, 1, 0, 0]).low_u64(), 0);
+
+        let mut b = [0; 32];
+        b[0] = 0x01;
+        b[31] = 0x02;
+        let x = U256::from_be_bytes(b);
+        assert_eq!(x.to_be_bytes(), b);
+        assert_eq!(x, U256([2, 0, 0, 1 << 56]));
+        assert_eq!(
+            U256::from_be_slice(&[0x01, 0x00]).unwrap(),
+            U256::from(256u64)
+        );
+        assert_eq!(U256::from_be_slice(&[0; 33]), Err(U256Error::Overflow));
+        assert!(U256::from(2u64) > U256::ONE);
+        assert!(U256([0, 0, 0, 1]) > U256([u64::MAX, u64::MAX, u64::MAX, 0]));
+    }
+
+    #[test]
+    fn rlp() {
+        assert_eq!(to_hex(&encode(&U256::ZERO)), "80");
+        assert_eq!(to_hex(&encode(&U256::from(0x7Fu64))), "7f");
+        assert_eq!(to_hex(&encode(&U256::from(1024u64))), "820400");
+        assert_eq!(to_hex(&encode(&U256::MAX)), format!("a0{}", "f".repeat(64)));
+        for x in [U256::ZERO, U256::from(1024u64), U256::MAX] {
+            let enc = encode(&x);
+            assert_eq!(x.encoded_len(), enc.len());
+            assert_eq!(decode::<U256>(&enc).unwrap(), x);
+        }
+
+        let mut too_large = vec![0xA1, 0x01];
+        too_large.extend_from_slice(&[0; 32]);
+        assert_eq!(
+            decode::<U256>(&too_large),
+            Err(DecodeError::ValueTooLarge(0))
+        );
+        assert_eq!(
+            decode::<U256>(&[0x82, 0x00, 0x01]),
+            Err(DecodeError::NonCanonicalInteger(0))
+        );
+        assert_eq!(decode::<U256>(&[0xC0]), Err(DecodeError::UnexpectedList(0)));
+    }
+}
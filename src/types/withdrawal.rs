@@ -0,0 +1,14 @@
+use crate::{
+    rlp::{RlpDecodable, RlpEncodable},
+    trie::types::Address,
+};
+
+/// Withdrawal represents a validator withdrawal from the consensus layer,
+/// as introduced by EIP-4895.
+#[derive(Debug, Clone, Default, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct Withdrawal {
+    pub index: u64, // monotonically increasing identifier issued by consensus layer
+    pub validator_index: u64, // index of validator associated with withdrawal
+    pub address: Address, // target address for withdrawn ether
+    pub amount: u64, // value of withdrawal in Gwei
+}
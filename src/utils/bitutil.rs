@@ -0,0 +1,138 @@
+// Compression of sparse bit vectors, as used by geth for the bloom bits of
+// the bloom trie.
+
+use std::fmt;
+
+/// BitutilError is returned for compressed data which can't be decompressed
+/// to the target size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitutilError {
+    /// The input references more bytes than it contains.
+    MissingData,
+    /// The input has bytes left after the last referenced one.
+    UnreferencedData,
+    /// The input is larger than the target, or references bytes past it.
+    ExceededTarget,
+    /// A referenced byte of the input is zero.
+    ZeroContent,
+}
+
+impl fmt::Display for BitutilError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            BitutilError::MissingData => "missing bytes on input",
+            BitutilError::UnreferencedData => "extra bytes on input",
+            BitutilError::ExceededTarget => "target data size exceeded",
+            BitutilError::ZeroContent => "zero byte in input content",
+        };
+        write!(f, "bitutil: {}", msg)
+    }
+}
+
+impl std::error::Error for BitutilError {}
+
+/// Compresses the input byte slice according to the sparse bitset
+/// representation algorithm. If the result is bigger than the original
+/// input, no compression is done.
+pub fn compress_bytes(data: &[u8]) -> Vec<u8> {
+    let out = bitset_encode_bytes(data);
+    if out.len() < data.len() {
+        return out;
+    }
+    data.to_vec()
+}
+
+/// Compresses the input byte slice according to the sparse bitset
+/// representation algorithm.
+fn bitset_encode_bytes(data: &[u8]) -> Vec<u8> {
+    // Empty slices get compressed to nil
+    if data.is_empty() {
+        return Vec::new();
+    }
+    // One byte slices compress to nil or retain the single byte
+    if data.len() == 1 {
+        if data[0] == 0 {
+            return Vec::new();
+        }
+        return data.to_vec();
+    }
+    // Calculate the bitset of set bytes, and gather the non-zero bytes
+    let mut non_zero_bitset = vec![0u8; data.len().div_ceil(8)];
+    let mut non_zero_bytes = Vec::with_capacity(data.len());
+    for (i, &b) in data.iter().enumerate() {
+        if b != 0 {
+            non_zero_bytes.push(b);
+            non_zero_bitset[i / 8] |= 1 << (7 - i % 8);
+        }
+    }
+    if non_zero_bytes.is_empty() {
+        return Vec::new();
+    }
+    let mut out = bitset_encode_bytes(&non_zero_bitset);
+    out.extend(non_zero_bytes);
+    out
+}
+
+/// Decompresses data with a known target size. If the input data matches the
+/// size of the target, it means no compression was done in the first place.
+pub fn decompress_bytes(data: &[u8], target: usize) -> Result<Vec<u8>, BitutilError> {
+    if data.len() > target {
+        return Err(BitutilError::ExceededTarget);
+    }
+    if data.len() == target {
+        return Ok(data.to_vec());
+    }
+    bitset_decode_bytes(data, target)
+}
+
+/// Decompresses data with a known target size.
+fn bitset_decode_bytes(data: &[u8], target: usize) -> Result<Vec<u8>, BitutilError> {
+    let (out, size) = bitset_decode_partial_bytes(data, target)?;
+    if size != data.len() {
+        return Err(BitutilError::UnreferencedData);
+    }
+    Ok(out)
+}
+
+/// Decompresses data with a known target size, but does not enforce
+/// consuming all the input bytes. In addition to the decompressed output,
+/// the function returns the length of compressed input data corresponding to
+/// the output as the input slice may be longer than necessary.
+fn bitset_decode_partial_bytes(data: &[u8], target: usize) -> Result<(Vec<u8>, usize), BitutilError> {
+    // Sanity check 0 targets to avoid infinite recursion
+    if target == 0 {
+        return Ok((Vec::new(), 0));
+    }
+    // Handle the zero and single byte corner cases
+    let mut decomp = vec![0u8; target];
+    if data.is_empty() {
+        return Ok((decomp, 0));
+    }
+    if target == 1 {
+        decomp[0] = data[0];
+        if data[0] != 0 {
+            return Ok((decomp, 1));
+        }
+        return Ok((decomp, 0));
+    }
+    // Decompress the bitset of set bytes and distribute the non zero bytes
+    let (non_zero_bitset, mut ptr) = bitset_decode_partial_bytes(data, target.div_ceil(8))?;
+    for i in 0..8 * non_zero_bitset.len() {
+        if non_zero_bitset[i / 8] & (1 << (7 - i % 8)) != 0 {
+            // Make sure we have enough data to push into the correct slot
+            if ptr >= data.len() {
+                return Err(BitutilError::MissingData);
+            }
+            if i >= decomp.len() {
+                return Err(BitutilError::ExceededTarget);
+            }
+            // Make sure the data is valid and push into the slot
+            if data[ptr] == 0 {
+                return Err(BitutilError::ZeroContent);
+            }
+            decomp[i] = data[ptr];
+            ptr += 1;
+        }
+    }
+    Ok((decomp, ptr))
+}
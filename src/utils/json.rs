@@ -0,0 +1,360 @@
+//! A minimal JSON value with a parser and a writer, for the JSON formats the
+//! crate reads and produces: RLP items, genesis files and account proofs.
+
+use std::fmt::{self, Write};
+
+/// The maximum nesting depth of arrays and objects. Parsing deeper input
+/// fails instead of overflowing the stack.
+const MAX_DEPTH: usize = 128;
+
+/// Json is a parsed JSON value. Numbers are kept as written, objects keep
+/// the order of their members.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+/// JsonError is returned for input which is not valid JSON. The offset is the
+/// byte position at which parsing stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct JsonError {
+    pub(crate) offset: usize,
+}
+
+impl Json {
+    pub(crate) fn parse(s: &str) -> Result<Json, JsonError> {
+        let mut p = Parser {
+            s,
+            pos: 0,
+            depth: 0,
+        };
+        let value = p.value().ok_or(JsonError { offset: p.pos })?;
+        p.skip_whitespace();
+        if p.pos != s.len() {
+            return Err(JsonError { offset: p.pos });
+        }
+
+        Ok(value)
+    }
+
+    /// Returns the member key of an object.
+    pub(crate) fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_object(&self) -> Option<&[(String, Json)]> {
+        match self {
+            Json::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+}
+
+fn write_string(f: &mut impl Write, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) => f.write_str(n),
+            Json::String(s) => write_string(f, s),
+            Json::Array(items) => {
+                f.write_char('[')?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_char(']')
+            }
+            Json::Object(members) => {
+                f.write_char('{')?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ": {}", value)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+    depth: usize, // number of arrays and objects around the position
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    /// Consumes word if the input continues with it.
+    fn literal(&mut self, word: &str) -> Option<()> {
+        if !self.s[self.pos..].starts_with(word) {
+            return None;
+        }
+        self.pos += word.len();
+        Some(())
+    }
+
+    fn value(&mut self) -> Option<Json> {
+        self.skip_whitespace();
+        match self.peek()? {
+            c @ ('[' | '{') => {
+                if self.depth == MAX_DEPTH {
+                    return None;
+                }
+                self.depth += 1;
+                let value = if c == '[' {
+                    self.array()
+                } else {
+                    self.object()
+                };
+                self.depth -= 1;
+                value
+            }
+            '"' => Some(Json::String(self.string()?)),
+            'n' => self.literal("null").map(|_| Json::Null),
+            't' => self.literal("true").map(|_| Json::Bool(true)),
+            'f' => self.literal("false").map(|_| Json::Bool(false)),
+            '-' | '0'..='9' => {
+                let start = self.pos;
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+                {
+                    self.pos += 1;
+                }
+                Some(Json::Number(self.s[start..self.pos].to_string()))
+            }
+            _ => None,
+        }
+    }
+
+    fn array(&mut self) -> Option<Json> {
+        self.next(); // '['
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.next();
+            return Some(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Some(Json::Array(items)),
+                _ => return None,
+            }
+        }
+    }
+
+    fn object(&mut self) -> Option<Json> {
+        self.next(); // '{'
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.next();
+            return Some(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return None;
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            if self.next() != Some(':') {
+                return None;
+            }
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Some(Json::Object(members)),
+                _ => return None,
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.next(); // '"'
+        let mut s = String::new();
+        loop {
+            match self.next()? {
+                '"' => return Some(s),
+                '\\' => {
+                    let c = match self.next()? {
+                        '"' => '"',
+                        '\\' => '\\',
+                        '/' => '/',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => {
+                            let hex = self.s.get(self.pos..self.pos + 4)?;
+                            self.pos += 4;
+                            let code = u32::from_str_radix(hex, 16).ok()?;
+                            char::from_u32(code)?
+                        }
+                        _ => return None,
+                    };
+                    s.push(c);
+                }
+                c => s.push(c),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nested(depth: usize) -> String {
+        "[".repeat(depth) + &"]".repeat(depth)
+    }
+
+    #[test]
+    fn parse_values() {
+        let json = Json::parse(r#" {"a": [1, -2.5e3, true, false, null], "b": {}, "c": []} "#);
+        assert_eq!(
+            json,
+            Ok(Json::Object(vec![
+                (
+                    "a".to_string(),
+                    Json::Array(vec![
+                        Json::Number("1".to_string()),
+                        Json::Number("-2.5e3".to_string()),
+                        Json::Bool(true),
+                        Json::Bool(false),
+                        Json::Null,
+                    ])
+                ),
+                ("b".to_string(), Json::Object(Vec::new())),
+                ("c".to_string(), Json::Array(Vec::new())),
+            ]))
+        );
+        let json = json.unwrap();
+        assert_eq!(json.get("c"), Some(&Json::Array(Vec::new())));
+        assert_eq!(json.get("b").and_then(Json::as_object), Some(&[][..]));
+        assert_eq!(json.get("d"), None);
+        assert_eq!(json.get("a").unwrap().as_str(), None);
+    }
+
+    #[test]
+    fn strings() {
+        let json = Json::parse(r#""a\"\\\/\n\r\té\u0001""#).unwrap();
+        assert_eq!(json.as_str(), Some("a\"\\/\n\r\t\u{e9}\u{1}"));
+        assert_eq!(json.to_string(), r#""a\"\\/\n\r\té\u0001""#);
+        assert_eq!(Json::parse(&json.to_string()), Ok(json));
+
+        for bad in [r#""abc"#, r#""\x""#, r#""\u00""#, r#""\ud800""#] {
+            assert!(Json::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn display() {
+        let json = r#"{"a": [1, "x", null], "b": {"c": true}}"#;
+        assert_eq!(Json::parse(json).unwrap().to_string(), json);
+    }
+
+    #[test]
+    fn syntax_errors() {
+        for bad in [
+            "",
+            "[",
+            "[1,]",
+            "[1 2]",
+            "{",
+            r#"{"a"}"#,
+            r#"{"a": }"#,
+            r#"{a: 1}"#,
+            r#"{"a": 1,}"#,
+            "nul",
+            "tru",
+            "1 2",
+            "x",
+        ] {
+            assert!(Json::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn error_offsets() {
+        assert_eq!(Json::parse(""), Err(JsonError { offset: 0 }));
+        assert_eq!(Json::parse("[1,]"), Err(JsonError { offset: 3 }));
+        assert_eq!(Json::parse("1 2"), Err(JsonError { offset: 2 }));
+        assert_eq!(Json::parse(r#"{"a": tru}"#), Err(JsonError { offset: 6 }));
+    }
+
+    #[test]
+    fn depth_limit() {
+        let mut json = Json::parse(&nested(MAX_DEPTH)).unwrap();
+        for _ in 1..MAX_DEPTH {
+            let Json::Array(mut items) = json else {
+                panic!("expected an array");
+            };
+            json = items.pop().unwrap();
+        }
+        assert_eq!(json, Json::Array(Vec::new()));
+
+        assert!(Json::parse(&nested(MAX_DEPTH + 1)).is_err());
+        assert!(Json::parse(&"{\"a\": ".repeat(MAX_DEPTH + 1)).is_err());
+        assert!(Json::parse(&"[".repeat(200_000)).is_err());
+
+        // Depth is counted along a path, not over the whole input.
+        let siblings = vec![nested(MAX_DEPTH - 1); 3].join(", ");
+        assert!(Json::parse(&format!("[{}]", siblings)).is_ok());
+    }
+}
@@ -1 +1,68 @@
-pub fn bytes_to_hash(b: Vec<u8>) {}
+pub mod bitutil;
+pub(crate) mod json;
+
+use sha3::{Digest, Keccak256};
+
+use crate::trie::types::{Hash, HASH_LENGTH};
+
+/// Converts a byte slice to a `Hash`. If `b` is larger than the hash length it is
+/// cropped from the left, if smaller it is left-padded with zeroes.
+pub fn bytes_to_hash(b: &[u8]) -> Hash {
+    let mut hash: Hash = [0; HASH_LENGTH];
+    let b = if b.len() > HASH_LENGTH {
+        &b[b.len() - HASH_LENGTH..]
+    } else {
+        b
+    };
+    hash[HASH_LENGTH - b.len()..].copy_from_slice(b);
+
+    hash
+}
+
+/// Returns the lowercase hex encoding of `b`, without a `0x` prefix.
+pub fn to_hex(b: &[u8]) -> String {
+    b.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decodes a hex string with an optional `0x` prefix. An odd number of digits
+/// is accepted, the first digit being taken as the low nibble of the first byte.
+/// Returns `None` if `s` contains a character which is not a hex digit.
+pub fn from_hex(s: &str) -> Option<Vec<u8>> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    let mut digits = s.chars().map(|c| c.to_digit(16).map(|d| d as u8));
+
+    let mut out = Vec::with_capacity(s.len().div_ceil(2));
+    if s.len() % 2 == 1 {
+        out.push(digits.next().flatten()?);
+    }
+    while let Some(hi) = digits.next() {
+        let lo = digits.next().flatten()?;
+        out.push(hi? << 4 | lo);
+    }
+
+    Some(out)
+}
+
+/// Decodes a hex string of at most 32 bytes into a `Hash`, left padding it
+/// with zeroes like `bytes_to_hash`. Returns `None` for longer or invalid
+/// strings.
+pub fn hex_to_hash(s: &str) -> Option<Hash> {
+    let b = from_hex(s)?;
+    if b.len() > HASH_LENGTH {
+        return None;
+    }
+    Some(bytes_to_hash(&b))
+}
+
+/// Computes the Keccak-256 hash of data.
+pub fn keccak256(data: &[u8]) -> Hash {
+    let mut hasher = Keccak256::new();
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+/// Returns a subslice of b without leading zero bytes.
+pub fn trim_left_zeroes(b: &[u8]) -> &[u8] {
+    let start = b.iter().position(|&x| x != 0).unwrap_or(b.len());
+    &b[start..]
+}